@@ -0,0 +1,117 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use oxid::builtin::BuiltinFn;
+use oxid::interpreter::Interpreter;
+use oxid::memory::Memory;
+use oxid::parser::{Parser, TokenCollector};
+use oxid::sandbox::Capabilities;
+use oxid::statements::Statement;
+use oxid::tokenizer::tokenize;
+use oxid::vm;
+
+/// fizzbuzz counting the length of its output instead of printing it
+const FIZZBUZZ: &str = r#"
+let mut out_len = 0;
+for i in 1..20000 {
+    let mut line = "";
+    if i % 3 == 0 { line += "Fizz"; };
+    if i % 5 == 0 { line += "Buzz"; };
+    if line == "" { line = str(i); };
+    out_len += len(line);
+};
+"#;
+
+const CALLS: &str = r#"
+fn add(a: Number, b: Number) { a + b; }
+
+let mut total = 0;
+for i in 0..20000 {
+    total = add(total, i);
+};
+"#;
+
+/// Nested loops reading and writing variables declared several scopes out
+const NESTED_LOOPS: &str = r#"
+let mut total = 0;
+fn count(n: Number) {
+    let mut hits = 0;
+    for i in 0..n {
+        let row = i % 7;
+        for j in 0..n {
+            let col = j % 5;
+            if row == col { hits += 1; };
+            total += row + col;
+        };
+    };
+    hits;
+}
+count(150);
+"#;
+
+/// Builds up a long string and list and passes them around without changing them
+const LARGE_VALUES: &str = r#"
+let mut text = "";
+for i in 0..5000 {
+    text += "line of text ";
+};
+let items = [text, text, text];
+
+fn pass(s: String) { s; }
+
+let mut total = 0;
+for i in 0..5000 {
+    let copy = items;
+    let back = pass(text);
+    total += len(copy);
+};
+"#;
+
+fn parse(buf: &str) -> Vec<Statement> {
+    let mut sim_memory = Memory::new();
+    BuiltinFn::populate_sim_memory(&mut sim_memory, &Capabilities::default());
+
+    let tokens = tokenize("bench.ox", buf).unwrap();
+    Parser::new(TokenCollector::new(&tokens), &mut sim_memory).generate_program()
+}
+
+fn bench(name: &str, f: impl Fn()) -> Duration {
+    let iterations = 10;
+    let mut total = Duration::ZERO;
+
+    for _ in 0..iterations {
+        let start = Instant::now();
+        f();
+        total += start.elapsed();
+    }
+
+    let per_iter = total / iterations;
+    println!("{:<24} {:>10.2?}/iter", name, per_iter);
+    per_iter
+}
+
+fn main() {
+    for (name, buf) in [("fizzbuzz", FIZZBUZZ), ("calls", CALLS), ("nested loops", NESTED_LOOPS), ("large values", LARGE_VALUES)] {
+        let statements = parse(buf);
+        let program = vm::compile(&statements);
+
+        let run = |use_vm: bool| {
+            let mut memory = Memory::new();
+            BuiltinFn::populate_memory(&mut memory, &Capabilities::default());
+
+            let mut interpreter = Interpreter::new(&statements, &mut memory, Capabilities::default());
+            let res = match use_vm {
+                true => vm::run(&mut interpreter, &program),
+                false => interpreter.run_program()
+            };
+            res.unwrap();
+
+            drop(interpreter);
+            black_box(memory);
+        };
+
+        let tree = bench(&format!("{} (tree)", name), || run(false));
+        let vm = bench(&format!("{} (vm)", name), || run(true));
+        println!("{:<24} {:>10.2}x\n", "speedup", tree.as_secs_f64() / vm.as_secs_f64());
+    }
+}
@@ -0,0 +1,57 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use oxid::builtin::BuiltinFn;
+use oxid::memory::Memory;
+use oxid::parser::{Parser, TokenCollector};
+use oxid::sandbox::Capabilities;
+use oxid::tokenizer::tokenize;
+
+const SNIPPET: &str = r#"
+/// prints fizzbuzz up to `n`
+fn fizzbuzz(n: Number) {
+    for i in 1..n {
+        let mut out = "";
+        if i % 3 == 0 { out += "Fizz"; };
+        if i % 5 == 0 { out += "Buzz"; };
+        /* fall back to the number */
+        if out == "" { out = str(i); };
+        print(out);
+    };
+}
+"#;
+
+fn bench(name: &str, buf: &str, f: impl Fn(&str) -> usize) {
+    let iterations = 10;
+    let mut total = Duration::ZERO;
+
+    for _ in 0..iterations {
+        let start = Instant::now();
+        black_box(f(black_box(buf)));
+        total += start.elapsed();
+    }
+
+    let per_iter = total / iterations;
+    let mb_per_sec = buf.len() as f64 / per_iter.as_secs_f64() / 1_000_000.0;
+    println!("{:<24} {:>10.2?}/iter {:>8.1} MB/s", name, per_iter, mb_per_sec);
+}
+
+/// Lexes and parses a whole program, returning the number of statements
+fn parse(buf: &str) -> usize {
+    let mut sim_memory = Memory::new();
+    BuiltinFn::populate_sim_memory(&mut sim_memory, &Capabilities::default());
+
+    let tokens = tokenize("bench.ox", buf).unwrap();
+    let mut parser = Parser::new(TokenCollector::new(&tokens), &mut sim_memory);
+    parser.parse_program().unwrap().len()
+}
+
+fn main() {
+    for copies in [1_000, 10_000] {
+        let buf = SNIPPET.repeat(copies);
+        println!("input: {} bytes", buf.len());
+
+        bench("tokenize", &buf, |buf| tokenize("bench.ox", buf).unwrap().len());
+        bench("tokenize and parse", &buf, parse);
+    }
+}
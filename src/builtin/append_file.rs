@@ -0,0 +1,29 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+
+use crate::data::Data;
+use crate::types::Type;
+
+use super::to_result_data;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [Type::String, Type::String] => Some(Type::Result(Box::new(Type::TempNil))),
+        _ => None
+    }
+}
+
+pub fn eval(args: Vec<Data>) -> Data {
+    match &args[..] {
+        [Data::String(path), Data::String(contents)] => {
+            let res = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path.as_str())
+                .and_then(|mut file| file.write_all(contents.as_bytes()));
+
+            to_result_data(res.map(|_| Data::TempNil))
+        },
+        _ => panic!("should have already been caught by the parser")
+    }
+}
@@ -0,0 +1,20 @@
+use std::rc::Rc;
+
+use crate::data::Data;
+use crate::interpreter::Interpreter;
+use crate::types::Type;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [] => Some(Type::List(Box::new(Type::String))),
+        _ => None
+    }
+}
+
+pub fn eval(interpreter: &mut Interpreter) -> Data {
+    let script_args = interpreter.script_args.iter()
+        .map(|arg| Data::String(Rc::new(arg.to_string())))
+        .collect();
+
+    Data::List(Rc::new(script_args))
+}
@@ -0,0 +1,41 @@
+use crate::data::Data;
+use crate::errors::RuntimeErr;
+use crate::types::Type;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [Type::Bool] | [Type::Bool, Type::String] => Some(Type::TempNil),
+        _ => None
+    }
+}
+
+pub fn eval(args: Vec<Data>) -> Result<Data, RuntimeErr> {
+    match &args[..] {
+        [Data::Bool(true), ..] => Ok(Data::TempNil),
+        [Data::Bool(false)] => Err(RuntimeErr::AssertionFailed(None)),
+        [Data::Bool(false), Data::String(msg)] => Err(RuntimeErr::AssertionFailed(Some(msg.to_string()))),
+        _ => panic!("should have already been caught by the parser")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::data::Data;
+    use crate::errors::RuntimeErr;
+    use crate::types::Type;
+
+    use super::{eval, type_check};
+
+    #[test]
+    fn assert_with_message() {
+        assert_eq!(type_check(vec![Type::Bool, Type::String]), Some(Type::TempNil));
+    }
+
+    #[test]
+    fn failed_assert() {
+        let res = eval(vec![Data::Bool(false), Data::String(Rc::new(String::from("oops")))]);
+        assert_eq!(res.unwrap_err(), RuntimeErr::AssertionFailed(Some(String::from("oops"))));
+    }
+}
@@ -0,0 +1,21 @@
+use std::env;
+use std::rc::Rc;
+
+use crate::data::Data;
+use crate::types::Type;
+
+use super::to_result_data;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [] => Some(Type::Result(Box::new(Type::String))),
+        _ => None
+    }
+}
+
+pub fn eval() -> Data {
+    let cwd = env::current_dir()
+        .map(|cwd| Data::String(Rc::new(cwd.to_string_lossy().to_string())));
+
+    to_result_data(cwd)
+}
@@ -0,0 +1,36 @@
+use std::env;
+use std::rc::Rc;
+
+use crate::data::Data;
+use crate::types::Type;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [Type::String] => Some(Type::Option(Box::new(Type::String))),
+        _ => None
+    }
+}
+
+pub fn eval(args: Vec<Data>) -> Data {
+    match args.first() {
+        Some(Data::String(name)) => Data::Option(
+            env::var(name.as_str()).ok().map(|value| Box::new(Data::String(Rc::new(value))))
+        ),
+        _ => panic!("should have already been caught by the parser")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::data::Data;
+
+    use super::eval;
+
+    #[test]
+    fn missing_variable_is_none() {
+        let res = eval(vec![Data::String(Rc::new(String::from("OXID_TEST_SURELY_UNSET")))]);
+        assert!(matches!(res, Data::Option(None)));
+    }
+}
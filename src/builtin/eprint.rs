@@ -0,0 +1,17 @@
+use std::io;
+
+use crate::data::Data;
+use crate::interpreter::Interpreter;
+use crate::types::Type;
+
+use super::print::write_data;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    super::print::type_check(args)
+}
+
+pub fn eval(args: Vec<Data>, interpreter: &mut Interpreter) -> Data {
+    interpreter.flush_stdout();
+    write_data(&mut io::stderr(), args.first().unwrap(), false);
+    Data::TempNil
+}
@@ -0,0 +1,21 @@
+use std::rc::Rc;
+
+use crate::data::Data;
+use crate::types::Type;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [Type::Result(_)] => Some(Type::Option(Box::new(Type::String))),
+        _ => None
+    }
+}
+
+/// The error message of a failed `Result`, `None` if it succeeded
+pub fn eval(args: Vec<Data>) -> Data {
+    match args.first() {
+        Some(Data::Result(val)) => Data::Option(
+            val.as_ref().err().map(|err| Box::new(Data::String(Rc::new(err.to_string()))))
+        ),
+        _ => panic!("should have already been caught by the parser")
+    }
+}
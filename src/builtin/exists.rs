@@ -0,0 +1,18 @@
+use std::path::Path;
+
+use crate::data::Data;
+use crate::types::Type;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [Type::String] => Some(Type::Bool),
+        _ => None
+    }
+}
+
+pub fn eval(args: Vec<Data>) -> Data {
+    match args.first() {
+        Some(Data::String(path)) => Data::Bool(Path::new(path.as_str()).exists()),
+        _ => panic!("should have already been caught by the parser")
+    }
+}
@@ -0,0 +1,20 @@
+use crate::data::Data;
+use crate::errors::RuntimeErr;
+use crate::types::Type;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [Type::Number] => Some(Type::TempNil),
+        _ => None
+    }
+}
+
+pub fn eval(args: Vec<Data>) -> Result<Data, RuntimeErr> {
+    match args.first() {
+        Some(Data::Number(code)) if code.fract() == 0.0 && (i32::MIN as f64..=i32::MAX as f64).contains(code) => {
+            Err(RuntimeErr::Exit(*code as i32))
+        },
+        Some(Data::Number(code)) => Err(RuntimeErr::InvalidExitCode(*code)),
+        _ => panic!("should have already been caught by the parser")
+    }
+}
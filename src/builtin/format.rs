@@ -0,0 +1,110 @@
+use std::rc::Rc;
+
+use crate::data::Data;
+use crate::errors::RuntimeErr;
+use crate::types::Type;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [Type::String, rest @ ..] if rest.iter().all(Type::is_displayable) => Some(Type::String),
+        _ => None
+    }
+}
+
+/// Number of `{}` placeholders in a template, escaped braces don't count
+pub fn placeholder_count(template: &str) -> usize {
+    let mut count = 0;
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+            },
+            ('{', Some('}')) => {
+                chars.next();
+                count += 1;
+            },
+            _ => ()
+        }
+    }
+
+    count
+}
+
+/// Replaces each `{}` in the template with the next argument, `{{` and `}}` escape the braces.
+/// Templates that are string literals have their placeholders counted by the parser already.
+pub fn eval(args: Vec<Data>) -> Result<Data, RuntimeErr> {
+    let mut args = args.into_iter();
+
+    let template = match args.next() {
+        Some(Data::String(template)) => template,
+        _ => panic!("should have already been caught by the parser")
+    };
+
+    let placeholders = placeholder_count(&template);
+    if placeholders != args.len() {
+        return Err(RuntimeErr::FormatArgCount(placeholders, args.len()));
+    }
+
+    let mut out = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                out.push(c);
+            },
+            ('{', Some('}')) => {
+                chars.next();
+                out.push_str(&args.next().unwrap().to_string());
+            },
+            _ => out.push(c)
+        }
+    }
+
+    Ok(Data::String(Rc::new(out)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::data::Data;
+    use crate::errors::RuntimeErr;
+    use crate::types::Type;
+
+    use super::{eval, placeholder_count, type_check};
+
+    #[test]
+    fn format_args() {
+        let res = eval(vec![
+            Data::String(Rc::new(String::from("{} is {}"))),
+            Data::String(Rc::new(String::from("x"))),
+            Data::Number(3.0)
+        ]);
+        assert!(matches!(res, Ok(Data::String(s)) if s.as_str() == "x is 3"));
+    }
+
+    #[test]
+    fn format_escaped_braces() {
+        let res = eval(vec![Data::String(Rc::new(String::from("{{{}}}"))), Data::Bool(false)]);
+        assert!(matches!(res, Ok(Data::String(s)) if s.as_str() == "{false}"));
+        assert_eq!(placeholder_count("{{}} {} }}{{"), 1);
+    }
+
+    #[test]
+    fn format_wrong_arg_count() {
+        let res = eval(vec![Data::String(Rc::new(String::from("{} {}"))), Data::Number(1.0)]);
+        assert_eq!(res.unwrap_err(), RuntimeErr::FormatArgCount(2, 1));
+
+        let res = eval(vec![Data::String(Rc::new(String::from("{}"))), Data::Number(1.0), Data::Number(2.0)]);
+        assert_eq!(res.unwrap_err(), RuntimeErr::FormatArgCount(1, 2));
+    }
+
+    #[test]
+    fn cannot_format_without_template() {
+        assert!(type_check(vec![Type::Number, Type::String]).is_none())
+    }
+}
@@ -0,0 +1,28 @@
+use crate::data::Data;
+use crate::types::Type;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [Type::Result(_)] => Some(Type::Bool),
+        _ => None
+    }
+}
+
+pub fn eval(args: Vec<Data>) -> Data {
+    match args.first() {
+        Some(Data::Result(val)) => Data::Bool(val.is_ok()),
+        _ => panic!("should have already been caught by the parser")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::Type;
+
+    use super::type_check;
+
+    #[test]
+    fn cannot_is_ok_option() {
+        assert!(type_check(vec![Type::Option(Box::new(Type::Number))]).is_none())
+    }
+}
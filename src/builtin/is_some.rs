@@ -0,0 +1,34 @@
+use crate::data::Data;
+use crate::types::Type;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [Type::Option(_)] => Some(Type::Bool),
+        _ => None
+    }
+}
+
+pub fn eval(args: Vec<Data>) -> Data {
+    match args.first() {
+        Some(Data::Option(val)) => Data::Bool(val.is_some()),
+        _ => panic!("should have already been caught by the parser")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::Type;
+
+    use super::type_check;
+
+    #[test]
+    fn is_some_option() {
+        let res_type = type_check(vec![Type::Option(Box::new(Type::Number))]);
+        assert_eq!(res_type, Some(Type::Bool));
+    }
+
+    #[test]
+    fn cannot_is_some_number() {
+        assert!(type_check(vec![Type::Number]).is_none())
+    }
+}
@@ -0,0 +1,40 @@
+use crate::data::Data;
+use crate::types::Type;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [Type::String | Type::List(_)] => Some(Type::Number),
+        _ => None
+    }
+}
+
+pub fn eval(args: Vec<Data>) -> Data {
+    match args.first() {
+        Some(Data::String(val)) => Data::Number(val.chars().count() as f64),
+        Some(Data::List(items)) => Data::Number(items.len() as f64),
+        _ => panic!("should have already been caught by the parser")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::data::Data;
+    use crate::types::Type;
+
+    use super::{eval, type_check};
+
+    #[test]
+    fn len_string() {
+        assert_eq!(type_check(vec![Type::String]), Some(Type::Number));
+
+        let res = eval(vec![Data::String(Rc::new(String::from("héllo")))]);
+        assert!(matches!(res, Data::Number(n) if n == 5.0));
+    }
+
+    #[test]
+    fn cannot_len_number() {
+        assert!(type_check(vec![Type::Number]).is_none())
+    }
+}
@@ -0,0 +1,59 @@
+use std::fs;
+use std::io;
+use std::rc::Rc;
+
+use crate::data::Data;
+use crate::types::Type;
+
+use super::to_result_data;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [Type::String] => Some(Type::Result(Box::new(Type::List(Box::new(Type::String))))),
+        _ => None
+    }
+}
+
+/// Names of the entries in a directory, sorted so the output doesn't depend on the platform
+fn entry_names(path: &str) -> io::Result<Vec<String>> {
+    let mut names = fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().to_string()))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    names.sort();
+    Ok(names)
+}
+
+pub fn eval(args: Vec<Data>) -> Data {
+    match args.first() {
+        Some(Data::String(path)) => {
+            let names = entry_names(path)
+                .map(|names| Data::List(Rc::new(names.into_iter().map(|name| Data::String(Rc::new(name))).collect())));
+
+            to_result_data(names)
+        },
+        _ => panic!("should have already been caught by the parser")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::rc::Rc;
+
+    use crate::data::Data;
+
+    use super::eval;
+
+    #[test]
+    fn lists_sorted_entries() {
+        let dir = std::env::temp_dir().join(format!("oxid-test-list-dir-{}", std::process::id()));
+        fs::create_dir_all(dir.join("b")).unwrap();
+        fs::write(dir.join("a.ox"), "").unwrap();
+
+        let res = eval(vec![Data::String(Rc::new(dir.to_string_lossy().to_string()))]);
+        assert_eq!(res.to_string(), "Ok([a.ox, b])");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
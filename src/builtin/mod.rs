@@ -1,41 +1,233 @@
+use std::fmt;
+
 use crate::data::Data;
-use crate::errors::ParseErrKind;
+use crate::errors::RuntimeErr;
+use crate::interpreter::Interpreter;
 use crate::memory::Memory;
+use crate::sandbox::{Capabilities, Capability};
 use crate::types::Type;
 
 pub mod print;
+pub mod println;
+pub mod eprint;
+pub mod eprintln;
+pub mod read_line;
+pub mod read_all;
+pub mod string;
+pub mod parse_number;
+pub mod format;
+pub mod is_some;
+pub mod unwrap_or;
+pub mod unwrap;
+pub mod len;
+pub mod args;
+pub mod assert;
+pub mod exit;
+pub mod read_file;
+pub mod write_file;
+pub mod append_file;
+pub mod exists;
+pub mod list_dir;
+pub mod read_lines;
+pub mod is_ok;
+pub mod err_msg;
+pub mod env;
+pub mod set_env;
+pub mod cwd;
+pub mod run;
 
-const BUILTIN_FUNCTIONS: [(&'static str, Type, Data); 1] = [
+const BUILTIN_FUNCTIONS: [(&str, Type, Data); 28] = [
     ("print", Type::BuiltinFn(BuiltinFn::Print), Data::BuiltinFn(BuiltinFn::Print)),
+    ("println", Type::BuiltinFn(BuiltinFn::Println), Data::BuiltinFn(BuiltinFn::Println)),
+    ("eprint", Type::BuiltinFn(BuiltinFn::Eprint), Data::BuiltinFn(BuiltinFn::Eprint)),
+    ("eprintln", Type::BuiltinFn(BuiltinFn::Eprintln), Data::BuiltinFn(BuiltinFn::Eprintln)),
+    ("read_line", Type::BuiltinFn(BuiltinFn::ReadLine), Data::BuiltinFn(BuiltinFn::ReadLine)),
+    ("read_all", Type::BuiltinFn(BuiltinFn::ReadAll), Data::BuiltinFn(BuiltinFn::ReadAll)),
+    ("str", Type::BuiltinFn(BuiltinFn::Str), Data::BuiltinFn(BuiltinFn::Str)),
+    ("parse_number", Type::BuiltinFn(BuiltinFn::ParseNumber), Data::BuiltinFn(BuiltinFn::ParseNumber)),
+    ("format", Type::BuiltinFn(BuiltinFn::Format), Data::BuiltinFn(BuiltinFn::Format)),
+    ("is_some", Type::BuiltinFn(BuiltinFn::IsSome), Data::BuiltinFn(BuiltinFn::IsSome)),
+    ("unwrap_or", Type::BuiltinFn(BuiltinFn::UnwrapOr), Data::BuiltinFn(BuiltinFn::UnwrapOr)),
+    ("unwrap", Type::BuiltinFn(BuiltinFn::Unwrap), Data::BuiltinFn(BuiltinFn::Unwrap)),
+    ("len", Type::BuiltinFn(BuiltinFn::Len), Data::BuiltinFn(BuiltinFn::Len)),
+    ("args", Type::BuiltinFn(BuiltinFn::Args), Data::BuiltinFn(BuiltinFn::Args)),
+    ("assert", Type::BuiltinFn(BuiltinFn::Assert), Data::BuiltinFn(BuiltinFn::Assert)),
+    ("exit", Type::BuiltinFn(BuiltinFn::Exit), Data::BuiltinFn(BuiltinFn::Exit)),
+    ("read_file", Type::BuiltinFn(BuiltinFn::ReadFile), Data::BuiltinFn(BuiltinFn::ReadFile)),
+    ("write_file", Type::BuiltinFn(BuiltinFn::WriteFile), Data::BuiltinFn(BuiltinFn::WriteFile)),
+    ("append_file", Type::BuiltinFn(BuiltinFn::AppendFile), Data::BuiltinFn(BuiltinFn::AppendFile)),
+    ("exists", Type::BuiltinFn(BuiltinFn::Exists), Data::BuiltinFn(BuiltinFn::Exists)),
+    ("list_dir", Type::BuiltinFn(BuiltinFn::ListDir), Data::BuiltinFn(BuiltinFn::ListDir)),
+    ("read_lines", Type::BuiltinFn(BuiltinFn::ReadLines), Data::BuiltinFn(BuiltinFn::ReadLines)),
+    ("is_ok", Type::BuiltinFn(BuiltinFn::IsOk), Data::BuiltinFn(BuiltinFn::IsOk)),
+    ("err_msg", Type::BuiltinFn(BuiltinFn::ErrMsg), Data::BuiltinFn(BuiltinFn::ErrMsg)),
+    ("env", Type::BuiltinFn(BuiltinFn::Env), Data::BuiltinFn(BuiltinFn::Env)),
+    ("set_env", Type::BuiltinFn(BuiltinFn::SetEnv), Data::BuiltinFn(BuiltinFn::SetEnv)),
+    ("cwd", Type::BuiltinFn(BuiltinFn::Cwd), Data::BuiltinFn(BuiltinFn::Cwd)),
+    ("run", Type::BuiltinFn(BuiltinFn::Run), Data::BuiltinFn(BuiltinFn::Run)),
 ];
 
+/// Turns the outcome of a fallible operation into a `Result` value, keeping the error message
+fn to_result_data<E: fmt::Display>(res: Result<Data, E>) -> Data {
+    Data::Result(res.map(Box::new).map_err(|err| err.to_string()))
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum BuiltinFn {
     Print,
+    Println,
+    Eprint,
+    Eprintln,
+    ReadLine,
+    ReadAll,
+    Str,
+    ParseNumber,
+    Format,
+    IsSome,
+    UnwrapOr,
+    Unwrap,
+    Len,
+    Args,
+    Assert,
+    Exit,
+    ReadFile,
+    WriteFile,
+    AppendFile,
+    Exists,
+    ListDir,
+    ReadLines,
+    IsOk,
+    ErrMsg,
+    Env,
+    SetEnv,
+    Cwd,
+    Run,
 }
 
 impl BuiltinFn {
-    pub fn populate_memory(memory: &mut Memory<Data>) {
-        for (name, _, builtin_fn_data) in BUILTIN_FUNCTIONS {
+    /// The capability a script needs to use this builtin, if any
+    pub fn capability(&self) -> Option<Capability> {
+        match self {
+            BuiltinFn::ReadFile | BuiltinFn::WriteFile | BuiltinFn::AppendFile |
+            BuiltinFn::Exists | BuiltinFn::ListDir | BuiltinFn::ReadLines => Some(Capability::Filesystem),
+            BuiltinFn::Env | BuiltinFn::SetEnv | BuiltinFn::Cwd => Some(Capability::Env),
+            BuiltinFn::Run => Some(Capability::Process),
+            _ => None
+        }
+    }
+
+    pub fn is_allowed(&self, capabilities: &Capabilities) -> bool {
+        self.capability().is_none_or(|capability| capabilities.allows(capability))
+    }
+
+    pub fn from_name(name: &str) -> Option<BuiltinFn> {
+        BUILTIN_FUNCTIONS.into_iter()
+            .find(|(builtin_name, _, _)| *builtin_name == name)
+            .and_then(|(_, _, builtin_fn_data)| match builtin_fn_data {
+                Data::BuiltinFn(builtin_fn) => Some(builtin_fn),
+                _ => None
+            })
+    }
+
+    /// Builtins that only need the given capabilities
+    fn allowed_builtins(capabilities: &Capabilities) -> impl Iterator<Item = (&'static str, Type, Data)> + '_ {
+        BUILTIN_FUNCTIONS.into_iter()
+            .filter(|(_, _, builtin_fn_data)| {
+                matches!(builtin_fn_data, Data::BuiltinFn(builtin_fn) if builtin_fn.is_allowed(capabilities))
+            })
+    }
+
+    pub fn populate_memory(memory: &mut Memory<Data>, capabilities: &Capabilities) {
+        for (name, _, builtin_fn_data) in BuiltinFn::allowed_builtins(capabilities) {
             memory.insert(String::from(name), builtin_fn_data);
         }
     }
 
-    pub fn populate_sim_memory(memory: &mut Memory<Type>) {
-        for (name, builtin_fn_type, _) in BUILTIN_FUNCTIONS {
+    pub fn populate_sim_memory(memory: &mut Memory<Type>, capabilities: &Capabilities) {
+        for (name, builtin_fn_type, _) in BuiltinFn::allowed_builtins(capabilities) {
             memory.insert(String::from(name), builtin_fn_type);
         }
     }
 
-    pub fn type_check(&self, args: Vec<Type>) -> Type {
+    pub fn get_name(&self) -> &'static str {
+        BUILTIN_FUNCTIONS.iter()
+            .find(|(_, builtin_fn_type, _)| *builtin_fn_type == Type::BuiltinFn(self.clone()))
+            .map(|(name, _, _)| *name)
+            .unwrap()
+    }
+
+    /// Returns the type of the call, or `None` if the argument types are invalid for this builtin
+    pub fn type_check(&self, args: Vec<Type>) -> Option<Type> {
         match self {
-            BuiltinFn::Print => print::type_check(args)
+            BuiltinFn::Print => print::type_check(args),
+            BuiltinFn::Println => println::type_check(args),
+            BuiltinFn::Eprint => eprint::type_check(args),
+            BuiltinFn::Eprintln => eprintln::type_check(args),
+            BuiltinFn::ReadLine => read_line::type_check(args),
+            BuiltinFn::ReadAll => read_all::type_check(args),
+            BuiltinFn::Str => string::type_check(args),
+            BuiltinFn::ParseNumber => parse_number::type_check(args),
+            BuiltinFn::Format => format::type_check(args),
+            BuiltinFn::IsSome => is_some::type_check(args),
+            BuiltinFn::UnwrapOr => unwrap_or::type_check(args),
+            BuiltinFn::Unwrap => unwrap::type_check(args),
+            BuiltinFn::Len => len::type_check(args),
+            BuiltinFn::Args => args::type_check(args),
+            BuiltinFn::Assert => assert::type_check(args),
+            BuiltinFn::Exit => exit::type_check(args),
+            BuiltinFn::ReadFile => read_file::type_check(args),
+            BuiltinFn::WriteFile => write_file::type_check(args),
+            BuiltinFn::AppendFile => append_file::type_check(args),
+            BuiltinFn::Exists => exists::type_check(args),
+            BuiltinFn::ListDir => list_dir::type_check(args),
+            BuiltinFn::ReadLines => read_lines::type_check(args),
+            BuiltinFn::IsOk => is_ok::type_check(args),
+            BuiltinFn::ErrMsg => err_msg::type_check(args),
+            BuiltinFn::Env => env::type_check(args),
+            BuiltinFn::SetEnv => set_env::type_check(args),
+            BuiltinFn::Cwd => cwd::type_check(args),
+            BuiltinFn::Run => run::type_check(args)
         }
     }
 
-    pub fn eval(&self, args: Vec<Data>) -> Data {
-        match self {
-            BuiltinFn::Print => print::eval(args)
+    pub fn eval(&self, args: Vec<Data>, interpreter: &mut Interpreter) -> Result<Data, RuntimeErr> {
+        // normally caught by the parser, but a builtin could have been registered regardless
+        if !self.is_allowed(&interpreter.capabilities) {
+            return Err(RuntimeErr::CapabilityDenied(self.get_name().to_string(), self.capability().unwrap()));
         }
+
+        let data = match self {
+            BuiltinFn::Print => print::eval(args, interpreter),
+            BuiltinFn::Println => println::eval(args, interpreter),
+            BuiltinFn::Eprint => eprint::eval(args, interpreter),
+            BuiltinFn::Eprintln => eprintln::eval(args, interpreter),
+            BuiltinFn::ReadLine => read_line::eval(interpreter),
+            BuiltinFn::ReadAll => read_all::eval(interpreter),
+            BuiltinFn::Str => string::eval(args),
+            BuiltinFn::ParseNumber => parse_number::eval(args),
+            BuiltinFn::Format => format::eval(args)?,
+            BuiltinFn::IsSome => is_some::eval(args),
+            BuiltinFn::UnwrapOr => unwrap_or::eval(args),
+            BuiltinFn::Unwrap => return unwrap::eval(args),
+            BuiltinFn::Len => len::eval(args),
+            BuiltinFn::Args => args::eval(interpreter),
+            BuiltinFn::Assert => return assert::eval(args),
+            BuiltinFn::Exit => return exit::eval(args),
+            BuiltinFn::ReadFile => read_file::eval(args),
+            BuiltinFn::WriteFile => write_file::eval(args),
+            BuiltinFn::AppendFile => append_file::eval(args),
+            BuiltinFn::Exists => exists::eval(args),
+            BuiltinFn::ListDir => list_dir::eval(args),
+            BuiltinFn::ReadLines => read_lines::eval(args),
+            BuiltinFn::IsOk => is_ok::eval(args),
+            BuiltinFn::ErrMsg => err_msg::eval(args),
+            BuiltinFn::Env => env::eval(args),
+            BuiltinFn::SetEnv => set_env::eval(args),
+            BuiltinFn::Cwd => cwd::eval(),
+            BuiltinFn::Run => run::eval(args, interpreter)
+        };
+
+        interpreter.check_memory(&data)?;
+        Ok(data)
     }
 }
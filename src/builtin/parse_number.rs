@@ -0,0 +1,47 @@
+use crate::data::Data;
+use crate::types::Type;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [Type::String] => Some(Type::Option(Box::new(Type::Number))),
+        _ => None
+    }
+}
+
+pub fn eval(args: Vec<Data>) -> Data {
+    match args.first() {
+        Some(Data::String(arg)) => {
+            let number = arg.trim().parse().ok()
+                .map(|number| Box::new(Data::Number(number)));
+            Data::Option(number)
+        },
+        _ => panic!("should have already been caught by the parser")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::data::Data;
+    use crate::types::Type;
+
+    use super::{eval, type_check};
+
+    #[test]
+    fn parse_valid_number() {
+        let res = eval(vec![Data::String(Rc::new(String::from(" 12.5 ")))]);
+        assert!(matches!(res, Data::Option(Some(number)) if matches!(*number, Data::Number(n) if n == 12.5)));
+    }
+
+    #[test]
+    fn parse_invalid_number() {
+        let res = eval(vec![Data::String(Rc::new(String::from("12a")))]);
+        assert!(matches!(res, Data::Option(None)));
+    }
+
+    #[test]
+    fn cannot_parse_number() {
+        assert!(type_check(vec![Type::Number]).is_none())
+    }
+}
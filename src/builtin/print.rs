@@ -1,20 +1,57 @@
-use crate::data::Data;
-use crate::errors::ParseErrKind;
+use std::io::Write;
 
+use crate::data::Data;
+use crate::interpreter::Interpreter;
 use crate::types::Type;
 
-pub fn type_check(_args: Vec<Type>) -> Type {
-    Type::TempNil
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [arg] if arg.is_displayable() => Some(Type::TempNil),
+        _ => None
+    }
 }
 
-pub fn eval(args: Vec<Data>) -> Data {
-    let arg = args.first().unwrap();
-    
-    match arg {
-        Data::String(arg) => println!("{}", arg),
-        Data::Number(arg) => println!("{:?}", arg),
-        _ => panic!("should have already been caught by the parser")
-    }
+/// Writes a value the way all print builtins show it, write errors like a closed pipe are ignored
+pub fn write_data(out: &mut dyn Write, arg: &Data, newline: bool) {
+    let end = if newline { "\n" } else { "" };
 
+    let _ = match arg {
+        Data::Fn(_) | Data::BuiltinFn(_) | Data::HostFn(_) | Data::Host(_) | Data::VmFn(_) | Data::TempNil => panic!("should have already been caught by the parser"),
+        _ => write!(out, "{}{}", arg, end)
+    };
+}
+
+pub fn eval(args: Vec<Data>, interpreter: &mut Interpreter) -> Data {
+    write_data(&mut interpreter.stdout, args.first().unwrap(), false);
     Data::TempNil
 }
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::data::Data;
+    use crate::types::Type;
+
+    use super::{type_check, write_data};
+
+    #[test]
+    fn print_one_arg() {
+        assert_eq!(type_check(vec![Type::String]), Some(Type::TempNil));
+    }
+
+    #[test]
+    fn cannot_print_nothing() {
+        assert!(type_check(vec![]).is_none())
+    }
+
+    #[test]
+    fn numbers_print_like_str_formats_them() {
+        let mut out = vec![];
+        write_data(&mut out, &Data::Number(2.0), true);
+        write_data(&mut out, &Data::Number(2.5), true);
+        write_data(&mut out, &Data::String(Rc::new(String::from("a"))), false);
+
+        assert_eq!(String::from_utf8(out).unwrap(), "2\n2.5\na");
+    }
+}
@@ -0,0 +1,23 @@
+use std::io::{self, Read};
+use std::rc::Rc;
+
+use crate::data::Data;
+use crate::interpreter::Interpreter;
+use crate::types::Type;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [] => Some(Type::String),
+        _ => None
+    }
+}
+
+/// Reads everything left on stdin
+pub fn eval(interpreter: &mut Interpreter) -> Data {
+    interpreter.flush_stdout();
+
+    let mut buf = String::new();
+    let _ = io::stdin().read_to_string(&mut buf);
+
+    Data::String(Rc::new(buf))
+}
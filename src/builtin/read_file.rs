@@ -0,0 +1,36 @@
+use std::fs;
+use std::rc::Rc;
+
+use crate::data::Data;
+use crate::types::Type;
+
+use super::to_result_data;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [Type::String] => Some(Type::Result(Box::new(Type::String))),
+        _ => None
+    }
+}
+
+pub fn eval(args: Vec<Data>) -> Data {
+    match args.first() {
+        Some(Data::String(path)) => to_result_data(fs::read_to_string(path.as_str()).map(|contents| Data::String(Rc::new(contents)))),
+        _ => panic!("should have already been caught by the parser")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::data::Data;
+
+    use super::eval;
+
+    #[test]
+    fn missing_file_is_an_error_value() {
+        let res = eval(vec![Data::String(Rc::new(String::from("/definitely/not/a/file.ox")))]);
+        assert!(matches!(res, Data::Result(Err(_))));
+    }
+}
@@ -0,0 +1,30 @@
+use std::io;
+use std::rc::Rc;
+
+use crate::data::Data;
+use crate::interpreter::Interpreter;
+use crate::types::Type;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [] => Some(Type::Option(Box::new(Type::String))),
+        _ => None
+    }
+}
+
+/// Reads the next line of stdin without its line ending, `None` once stdin is exhausted
+pub fn eval(interpreter: &mut Interpreter) -> Data {
+    // flushed first so prompts printed without a newline show up before waiting for input
+    interpreter.flush_stdout();
+
+    let mut line = String::new();
+    match io::stdin().read_line(&mut line) {
+        Ok(0) | Err(_) => Data::Option(None),
+        Ok(_) => {
+            let line = line.strip_suffix('\n').unwrap_or(&line);
+            let line = line.strip_suffix('\r').unwrap_or(line);
+
+            Data::Option(Some(Box::new(Data::String(Rc::new(line.to_string())))))
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use std::fs;
+use std::rc::Rc;
+
+use crate::data::Data;
+use crate::types::Type;
+
+use super::to_result_data;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [Type::String] => Some(Type::Result(Box::new(Type::List(Box::new(Type::String))))),
+        _ => None
+    }
+}
+
+pub fn eval(args: Vec<Data>) -> Data {
+    match args.first() {
+        Some(Data::String(path)) => {
+            let lines = fs::read_to_string(path.as_str())
+                .map(|contents| Data::List(Rc::new(contents.lines().map(|line| Data::String(Rc::new(line.to_string()))).collect())));
+
+            to_result_data(lines)
+        },
+        _ => panic!("should have already been caught by the parser")
+    }
+}
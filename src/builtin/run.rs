@@ -0,0 +1,62 @@
+use std::process::Command;
+use std::rc::Rc;
+
+use crate::data::Data;
+use crate::interpreter::Interpreter;
+use crate::types::Type;
+
+use super::to_result_data;
+
+/// Type of what a finished process returns
+pub fn output_type() -> Type {
+    Type::Record(vec![
+        (String::from("status"), Type::Number),
+        (String::from("stdout"), Type::String),
+        (String::from("stderr"), Type::String)
+    ])
+}
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [Type::String, Type::List(arg_type)] if matches!(**arg_type, Type::String | Type::TempNil) => {
+            Some(Type::Result(Box::new(output_type())))
+        },
+        _ => None
+    }
+}
+
+/// Runs a program to completion and captures its output, the status is `-1` if it was killed by
+/// a signal
+pub fn eval(args: Vec<Data>, interpreter: &mut Interpreter) -> Data {
+    match &args[..] {
+        [Data::String(cmd), Data::List(cmd_args)] => {
+            interpreter.flush_stdout();
+
+            let cmd_args = cmd_args.iter().map(|arg| arg.to_string());
+            let output = Command::new(cmd.as_str()).args(cmd_args).output().map(|output| {
+                Data::Record(Rc::new(vec![
+                    (String::from("status"), Data::Number(output.status.code().unwrap_or(-1) as f64)),
+                    (String::from("stdout"), Data::String(Rc::new(String::from_utf8_lossy(&output.stdout).to_string()))),
+                    (String::from("stderr"), Data::String(Rc::new(String::from_utf8_lossy(&output.stderr).to_string())))
+                ]))
+            });
+
+            to_result_data(output)
+        },
+        _ => panic!("should have already been caught by the parser")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::Type;
+
+    use super::type_check;
+
+    #[test]
+    fn run_takes_string_args() {
+        assert!(type_check(vec![Type::String, Type::List(Box::new(Type::String))]).is_some());
+        assert!(type_check(vec![Type::String, Type::List(Box::new(Type::TempNil))]).is_some());
+        assert!(type_check(vec![Type::String, Type::List(Box::new(Type::Number))]).is_none());
+    }
+}
@@ -0,0 +1,21 @@
+use std::env;
+
+use crate::data::Data;
+use crate::types::Type;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [Type::String, Type::String] => Some(Type::TempNil),
+        _ => None
+    }
+}
+
+pub fn eval(args: Vec<Data>) -> Data {
+    match &args[..] {
+        [Data::String(name), Data::String(value)] => {
+            env::set_var(name.as_str(), value.as_str());
+            Data::TempNil
+        },
+        _ => panic!("should have already been caught by the parser")
+    }
+}
@@ -0,0 +1,46 @@
+use std::rc::Rc;
+
+use crate::data::Data;
+use crate::types::Type;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [arg] if arg.is_displayable() => Some(Type::String),
+        _ => None
+    }
+}
+
+pub fn eval(args: Vec<Data>) -> Data {
+    let arg = args.first().unwrap();
+    Data::String(Rc::new(arg.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::Data;
+    use crate::types::Type;
+
+    use super::{eval, type_check};
+
+    #[test]
+    fn str_number() {
+        assert_eq!(type_check(vec![Type::Number]), Some(Type::String));
+
+        let res = eval(vec![Data::Number(42.0)]);
+        assert!(matches!(res, Data::String(s) if s.as_str() == "42"));
+
+        let res = eval(vec![Data::Number(0.5)]);
+        assert!(matches!(res, Data::String(s) if s.as_str() == "0.5"));
+    }
+
+    #[test]
+    fn str_bool() {
+        let res = eval(vec![Data::Bool(true)]);
+        assert!(matches!(res, Data::String(s) if s.as_str() == "true"));
+    }
+
+    #[test]
+    fn cannot_str_many() {
+        assert!(type_check(vec![Type::Number, Type::Number]).is_none())
+    }
+}
@@ -0,0 +1,34 @@
+use crate::data::Data;
+use crate::errors::RuntimeErr;
+use crate::types::Type;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [Type::Option(inner) | Type::Result(inner)] => Some(*inner.clone()),
+        _ => None
+    }
+}
+
+/// The value of a `Some` or `Ok`, stopping the program on `None` or `Err`
+pub fn eval(args: Vec<Data>) -> Result<Data, RuntimeErr> {
+    match args.into_iter().next() {
+        Some(Data::Option(Some(val)) | Data::Result(Ok(val))) => Ok(*val),
+        Some(Data::Option(None)) => Err(RuntimeErr::UnwrapFailed(String::from("None"))),
+        Some(Data::Result(Err(err))) => Err(RuntimeErr::UnwrapFailed(format!("Err({})", err))),
+        _ => panic!("should have already been caught by the parser")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::Data;
+    use crate::errors::RuntimeErr;
+
+    use super::eval;
+
+    #[test]
+    fn unwrap_err_fails() {
+        let res = eval(vec![Data::Result(Err(String::from("oops")))]);
+        assert_eq!(res.unwrap_err(), RuntimeErr::UnwrapFailed(String::from("Err(oops)")));
+    }
+}
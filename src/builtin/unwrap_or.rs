@@ -0,0 +1,37 @@
+use crate::data::Data;
+use crate::types::Type;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [Type::Option(inner) | Type::Result(inner), default] if **inner == *default => Some(default.clone()),
+        _ => None
+    }
+}
+
+pub fn eval(args: Vec<Data>) -> Data {
+    let mut args = args.into_iter();
+
+    match (args.next(), args.next()) {
+        (Some(Data::Option(val)), Some(default)) => val.map(|val| *val).unwrap_or(default),
+        (Some(Data::Result(val)), Some(default)) => val.map(|val| *val).unwrap_or(default),
+        _ => panic!("should have already been caught by the parser")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::Type;
+
+    use super::type_check;
+
+    #[test]
+    fn unwrap_or_same_type() {
+        let res_type = type_check(vec![Type::Option(Box::new(Type::Number)), Type::Number]);
+        assert_eq!(res_type, Some(Type::Number));
+    }
+
+    #[test]
+    fn cannot_unwrap_or_different() {
+        assert!(type_check(vec![Type::Option(Box::new(Type::Number)), Type::String]).is_none())
+    }
+}
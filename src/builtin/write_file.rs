@@ -0,0 +1,52 @@
+use std::fs;
+
+use crate::data::Data;
+use crate::types::Type;
+
+use super::to_result_data;
+
+pub fn type_check(args: Vec<Type>) -> Option<Type> {
+    match &args[..] {
+        [Type::String, Type::String] => Some(Type::Result(Box::new(Type::TempNil))),
+        _ => None
+    }
+}
+
+pub fn eval(args: Vec<Data>) -> Data {
+    match &args[..] {
+        [Data::String(path), Data::String(contents)] => {
+            to_result_data(fs::write(path.as_str(), contents.as_str()).map(|_| Data::TempNil))
+        },
+        _ => panic!("should have already been caught by the parser")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::rc::Rc;
+
+    use crate::builtin::{append_file, read_file, read_lines};
+    use crate::data::Data;
+
+    use super::eval;
+
+    #[test]
+    fn write_append_and_read_back() {
+        let path = std::env::temp_dir().join(format!("oxid-test-write-{}.txt", std::process::id()));
+        let path_data = Data::String(Rc::new(path.to_string_lossy().to_string()));
+
+        let res = eval(vec![path_data.clone(), Data::String(Rc::new(String::from("a\n")))]);
+        assert!(matches!(res, Data::Result(Ok(_))));
+
+        append_file::eval(vec![path_data.clone(), Data::String(Rc::new(String::from("b\n")))]);
+
+        let contents = read_file::eval(vec![path_data.clone()]);
+        assert_eq!(contents.to_string(), "Ok(a\nb\n)");
+
+        let lines = read_lines::eval(vec![path_data]);
+        assert_eq!(lines.to_string(), "Ok([a, b])");
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -1,20 +1,33 @@
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
+use std::rc::Rc;
+
 use crate::builtin::BuiltinFn;
-use crate::errors::ParseErrKind;
+use crate::engine::{HostFn, HostObject};
+use crate::errors::RuntimeErr;
 use crate::interpreter::Interpreter;
-use crate::operations::Operation;
 use crate::parser::Parser;
-use crate::expressions::{Evaluable, Expr};
+use crate::expressions::Evaluable;
 use crate::statements::r#fn::FunctionDeclaration;
 use crate::types::Type;
+use crate::vm;
 
+/// A value at runtime. Strings, collections and functions are reference counted so cloning a
+/// value is cheap no matter its size, they're copied only when changed while shared.
 #[derive(Debug, Clone)]
 pub enum Data {
-    String(String),
+    String(Rc<String>),
     Number(f64),
     Bool(bool),
-    Fn(FunctionDeclaration),
+    Option(Option<Box<Data>>),
+    Result(Result<Box<Data>, String>),
+    List(Rc<Vec<Data>>),
+    Record(Rc<Vec<(String, Data)>>),
+    Fn(Rc<FunctionDeclaration>),
     BuiltinFn(BuiltinFn),
+    HostFn(HostFn),
+    Host(HostObject),
+    /// A function compiled for the VM backend
+    VmFn(Rc<vm::Function>),
     TempNil // just a temporary null value in the meantime as we dont have empty tuples yet
 }
 
@@ -24,16 +37,77 @@ impl Data {
             Data::String(_val) => Type::String,
             Data::Number(_val) => Type::Number,
             Data::Bool(_val) => Type::Bool,
+            Data::Option(val) => Type::Option(Box::new(
+                val.as_ref().map(|val| val.get_type()).unwrap_or(Type::TempNil)
+            )),
+            Data::Result(val) => Type::Result(Box::new(
+                val.as_ref().map(|val| val.get_type()).unwrap_or(Type::TempNil)
+            )),
+            Data::List(items) => Type::List(Box::new(
+                items.first().map(|item| item.get_type()).unwrap_or(Type::TempNil)
+            )),
+            Data::Record(fields) => Type::Record(
+                fields.iter().map(|(name, field)| (name.to_string(), field.get_type())).collect()
+            ),
             Data::Fn(val) => Type::Fn { args_types: val.args.clone(), return_type: val.return_type.clone() },
             Data::BuiltinFn(builtin_fn) => Type::BuiltinFn(builtin_fn.clone()),
+            Data::HostFn(host_fn) => host_fn.get_type(),
+            Data::Host(object) => Type::Host(object.class.id),
+            Data::VmFn(function) => function.get_type(),
             Data::TempNil => Type::TempNil
         }
     }
 }
 
+impl Data {
+    /// Bytes owned by the value on the heap, roughly: the contents of strings and collections
+    /// but not the functions, which are shared with the program. Values shared between
+    /// variables are counted for each of them.
+    pub fn heap_size(&self) -> usize {
+        match self {
+            Data::String(val) => val.len(),
+            Data::Option(Some(val)) | Data::Result(Ok(val)) => size_of::<Data>() + val.heap_size(),
+            Data::Result(Err(err)) => err.len(),
+            Data::List(items) => items.iter().map(|item| size_of::<Data>() + item.heap_size()).sum(),
+            Data::Record(fields) => fields.iter()
+                .map(|(name, field)| size_of::<(String, Data)>() + name.len() + field.heap_size())
+                .sum(),
+            _ => 0
+        }
+    }
+}
+
+impl fmt::Display for Data {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Data::String(val) => write!(f, "{}", val),
+            Data::Number(val) => write!(f, "{}", val),
+            Data::Bool(val) => write!(f, "{}", val),
+            Data::Option(Some(val)) => write!(f, "Some({})", val),
+            Data::Option(None) => write!(f, "None"),
+            Data::Result(Ok(val)) => write!(f, "Ok({})", val),
+            Data::Result(Err(err)) => write!(f, "Err({})", err),
+            Data::List(items) => {
+                let items: Vec<_> = items.iter().map(|item| item.to_string()).collect();
+                write!(f, "[{}]", items.join(", "))
+            },
+            Data::Record(fields) => {
+                let fields: Vec<_> = fields.iter().map(|(name, field)| format!("{}: {}", name, field)).collect();
+                write!(f, "{{ {} }}", fields.join(", "))
+            },
+            Data::Fn(val) => write!(f, "<fn {}>", val.name),
+            Data::BuiltinFn(builtin_fn) => write!(f, "<builtin {}>", builtin_fn.get_name()),
+            Data::HostFn(host_fn) => write!(f, "<host fn {}>", host_fn.name),
+            Data::Host(object) => write!(f, "<{}>", object.class.id.name),
+            Data::VmFn(function) => write!(f, "<fn {}>", function.name),
+            Data::TempNil => write!(f, "()")
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ExprLiteral {
-    data: Data
+    pub data: Data
 }
 
 impl ExprLiteral {
@@ -49,8 +123,8 @@ impl Evaluable for ExprLiteral {
         self.data.get_type()
     }
 
-    fn eval(&self, _interpreter: &mut Interpreter) -> Data {
-        self.data.clone()
+    fn eval(&self, _interpreter: &mut Interpreter) -> Result<Data, RuntimeErr> {
+        Ok(self.data.clone())
     }
 }
 
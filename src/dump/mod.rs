@@ -0,0 +1,287 @@
+use std::fmt;
+
+use crate::data::Data;
+use crate::expressions::Expr;
+use crate::expressions::assign::AssignOp;
+use crate::expressions::block::ExprBlock;
+use crate::memory::Slot;
+use crate::statements::Statement;
+use crate::tokenizer::token::Token;
+use crate::visitor::{self, Visitor};
+
+/// How `--dump-ast` prints the syntax tree
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AstFormat {
+    /// One node per line, indented under its parent
+    Text,
+    /// Nested objects with a `kind`, the node's attributes and its `children`
+    Json
+}
+
+/// Lists the tokens one per line with the position they start at
+pub fn dump_tokens(tokens: &[Token]) -> String {
+    tokens.iter()
+        .map(|token| format!("{:<8} {:?}\n", token.token_pos.to_string(), token.token))
+        .collect()
+}
+
+pub fn dump_ast(statements: &[Statement], format: AstFormat) -> String {
+    let mut printer = AstPrinter {
+        format,
+        depth: 0,
+        lines: vec![],
+        children: vec![vec![]]
+    };
+
+    for statement in statements.iter() {
+        printer.visit_statement(statement);
+    }
+
+    match format {
+        AstFormat::Text => printer.lines.concat(),
+        AstFormat::Json => format!("[{}]\n", printer.children[0].join(", "))
+    }
+}
+
+/// A node of the syntax tree without its children
+struct Node {
+    kind: &'static str,
+    attrs: Vec<(&'static str, Attr)>
+}
+
+/// Value of an attribute of a node, kept typed so JSON gets numbers and booleans
+enum Attr {
+    Text(String),
+    /// A string literal, quoted in the text format
+    Str(String),
+    Number(f64),
+    Bool(bool)
+}
+
+impl Attr {
+    fn to_json(&self) -> String {
+        match self {
+            Attr::Text(val) | Attr::Str(val) => json_string(val),
+            Attr::Number(val) if val.is_finite() => val.to_string(),
+            // JSON has no infinity or NaN, which constant folding can produce
+            Attr::Number(_) => String::from("null"),
+            Attr::Bool(val) => val.to_string()
+        }
+    }
+}
+
+impl fmt::Display for Attr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Attr::Text(val) => write!(f, "{}", val),
+            Attr::Str(val) => write!(f, "{:?}", val),
+            Attr::Number(val) => write!(f, "{}", val),
+            Attr::Bool(val) => write!(f, "{}", val)
+        }
+    }
+}
+
+impl From<&str> for Attr {
+    fn from(val: &str) -> Self {
+        Attr::Text(val.to_string())
+    }
+}
+
+impl From<&String> for Attr {
+    fn from(val: &String) -> Self {
+        Attr::Text(val.to_string())
+    }
+}
+
+impl From<String> for Attr {
+    fn from(val: String) -> Self {
+        Attr::Text(val)
+    }
+}
+
+impl From<usize> for Attr {
+    fn from(val: usize) -> Self {
+        Attr::Number(val as f64)
+    }
+}
+
+impl From<bool> for Attr {
+    fn from(val: bool) -> Self {
+        Attr::Bool(val)
+    }
+}
+
+impl From<&Data> for Attr {
+    fn from(data: &Data) -> Self {
+        match data {
+            Data::String(val) => Attr::Str(val.to_string()),
+            Data::Number(val) => Attr::Number(*val),
+            Data::Bool(val) => Attr::Bool(*val),
+            data => Attr::Text(data.to_string())
+        }
+    }
+}
+
+impl Node {
+    fn new(kind: &'static str) -> Self {
+        Self {
+            kind,
+            attrs: vec![]
+        }
+    }
+
+    fn attr(mut self, name: &'static str, value: impl Into<Attr>) -> Self {
+        self.attrs.push((name, value.into()));
+        self
+    }
+
+    fn slot(self, slot: Option<Slot>) -> Self {
+        match slot {
+            Some(Slot::Local { depth, index }) => self.attr("slot", format!("local {}:{}", depth, index)),
+            Some(Slot::Global) => self.attr("slot", "global"),
+            None => self
+        }
+    }
+
+    fn statement(statement: &Statement) -> Self {
+        match statement {
+            Statement::VariableAssignment(var_assign) => Node::new("Let")
+                .attr("name", &var_assign.name)
+                .attr("mut", var_assign.is_mut)
+                .slot(var_assign.slot),
+            Statement::FunctionDeclaration(fn_decl) => {
+                let args: Vec<_> = fn_decl.args.iter()
+                    .map(|(arg_name, arg_type)| format!("{}: {}", arg_name, arg_type.get_name()))
+                    .collect();
+
+                Node::new("Fn")
+                    .attr("name", &fn_decl.name)
+                    .attr("args", args.join(", "))
+                    .attr("returns", fn_decl.return_type.get_name())
+            },
+            Statement::ModuleImport(mod_import) => Node::new("Mod").attr("name", &mod_import.name),
+            Statement::UseDeclaration(use_decl) => Node::new("Use")
+                .attr("path", &use_decl.path)
+                .attr("alias", &use_decl.alias),
+            Statement::Expr(_) => unreachable!("expression statements are printed as their expression")
+        }
+    }
+
+    fn expr(expr: &Expr) -> Self {
+        match expr {
+            Expr::Literal(literal_expr) => Node::new("Literal").attr("value", &literal_expr.data),
+            Expr::Binary(binary_expr) => Node::new("Binary").attr("op", format!("{:?}", binary_expr.operation)),
+            Expr::Index(_) => Node::new("Index"),
+            Expr::Unary(unary_expr) => Node::new("Unary").attr("op", format!("{:?}", unary_expr.op)),
+            Expr::Field(field_expr) => Node::new("Field").attr("name", &field_expr.field_name).slot(field_expr.slot),
+            Expr::Call(call_expr) => Node::new("Call").attr("args", call_expr.args.len()),
+            Expr::Assign(assign_expr) => Node::new("Assign").attr("op", match assign_expr.op {
+                AssignOp::Eq => "=",
+                AssignOp::AddEq => "+="
+            }),
+            Expr::Block(_) => Node::new("Block"),
+            Expr::For(for_expr) => Node::new("For").attr("index", &for_expr.index_var),
+            Expr::If(_) => Node::new("If"),
+            Expr::List(list_expr) => Node::new("List").attr("len", list_expr.items.len()),
+            Expr::Member(member_expr) => Node::new("Member").attr("field", &member_expr.field_name)
+        }
+    }
+
+    fn to_json(&self, children: &[String]) -> String {
+        let attrs: String = self.attrs.iter()
+            .map(|(name, value)| format!(", {}: {}", json_string(name), value.to_json()))
+            .collect();
+
+        format!("{{\"kind\": {}{}, \"children\": [{}]}}", json_string(self.kind), attrs, children.join(", "))
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+
+        for (name, value) in self.attrs.iter() {
+            write!(f, " {}={}", name, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+struct AstPrinter {
+    format: AstFormat,
+    depth: usize,
+    lines: Vec<String>,
+    /// Printed children of each JSON node still being visited, the first holds the statements
+    children: Vec<Vec<String>>
+}
+
+impl AstPrinter {
+    /// Prints `node` and everything `walk` visits under it
+    fn node<F>(&mut self, node: Node, walk: F)
+    where
+        F: FnOnce(&mut Self)
+    {
+        match self.format {
+            AstFormat::Text => {
+                self.lines.push(format!("{}{}\n", "  ".repeat(self.depth), node));
+                self.depth += 1;
+                walk(self);
+                self.depth -= 1;
+            },
+            AstFormat::Json => {
+                self.children.push(vec![]);
+                walk(self);
+                let children = self.children.pop().unwrap();
+                self.children.last_mut().unwrap().push(node.to_json(&children));
+            }
+        }
+    }
+}
+
+impl Visitor for AstPrinter {
+    fn visit_statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Expr(expr) => self.visit_expr(expr),
+            _ => self.node(Node::statement(statement), |printer| visitor::walk_statement(printer, statement))
+        }
+    }
+
+    fn visit_block(&mut self, block: &ExprBlock) {
+        self.node(Node::new("Block"), |printer| visitor::walk_block(printer, block));
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        self.node(Node::expr(expr), |printer| visitor::walk_expr(printer, expr));
+    }
+}
+
+fn json_string(val: &str) -> String {
+    let mut out = String::from("\"");
+
+    for c in val.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c)
+        }
+    }
+
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::json_string;
+
+    #[test]
+    fn json_strings_are_escaped() {
+        assert_eq!(json_string("a \"b\"\n\\"), "\"a \\\"b\\\"\\n\\\\\"");
+        assert_eq!(json_string("\u{1}é"), "\"\\u0001é\"");
+    }
+}
@@ -0,0 +1,140 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::data::Data;
+use crate::errors::Error;
+use crate::types::Type;
+use super::Value;
+
+/// A Rust type that can cross into oxid, mapping it to the type the parser checks it as
+pub trait HostType: TryFrom<Value, Error = Error> + Into<Value> {
+    fn oxid_type() -> Type;
+}
+
+impl HostType for () {
+    fn oxid_type() -> Type {
+        Type::TempNil
+    }
+}
+
+impl HostType for f64 {
+    fn oxid_type() -> Type {
+        Type::Number
+    }
+}
+
+impl HostType for bool {
+    fn oxid_type() -> Type {
+        Type::Bool
+    }
+}
+
+impl HostType for String {
+    fn oxid_type() -> Type {
+        Type::String
+    }
+}
+
+impl<T: HostType> HostType for Option<T> {
+    fn oxid_type() -> Type {
+        Type::Option(Box::new(T::oxid_type()))
+    }
+}
+
+impl<T: HostType> HostType for Vec<T> {
+    fn oxid_type() -> Type {
+        Type::List(Box::new(T::oxid_type()))
+    }
+}
+
+impl<T: HostType> HostType for Result<T, String> {
+    fn oxid_type() -> Type {
+        Type::Result(Box::new(T::oxid_type()))
+    }
+}
+
+/// A Rust function registered with [`crate::Engine::register_fn`], called like any other function
+#[derive(Clone)]
+pub struct HostFn {
+    pub name: String,
+    pub args_types: Vec<(String, Type)>,
+    pub return_type: Type,
+    func: Rc<dyn Fn(Vec<Data>) -> Data>
+}
+
+impl HostFn {
+    pub(crate) fn new(name: &str, args_types: Vec<(String, Type)>, return_type: Type, func: Rc<dyn Fn(Vec<Data>) -> Data>) -> Self {
+        Self {
+            name: name.to_string(),
+            args_types,
+            return_type,
+            func
+        }
+    }
+
+    pub fn get_type(&self) -> Type {
+        Type::Fn { args_types: self.args_types.clone(), return_type: Box::new(self.return_type.clone()) }
+    }
+
+    pub fn call(&self, args: Vec<Data>) -> Data {
+        (self.func)(args)
+    }
+
+    pub fn ptr_eq(&self, other: &HostFn) -> bool {
+        Rc::ptr_eq(&self.func, &other.func)
+    }
+}
+
+impl fmt::Debug for HostFn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HostFn")
+            .field("name", &self.name)
+            .field("args_types", &self.args_types)
+            .field("return_type", &self.return_type)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Closures that can be registered as functions, implemented for up to six arguments that are
+/// all [`HostType`]s. `Args` only tells the implementations apart.
+pub trait IntoHostFn<Args> {
+    fn into_host_fn(self, name: &str) -> HostFn;
+}
+
+/// Converts an argument the parser already checked the type of
+fn from_data<T: HostType>(data: Data) -> T {
+    T::try_from(Value::from(data)).expect("arguments should have been type checked by the parser")
+}
+
+macro_rules! impl_into_host_fn {
+    ($($arg:ident),*) => {
+        impl<F, R, $($arg),*> IntoHostFn<($($arg,)*)> for F
+        where
+            F: Fn($($arg),*) -> R + 'static,
+            R: HostType,
+            $($arg: HostType),*
+        {
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn into_host_fn(self, name: &str) -> HostFn {
+                let args_types = vec![$((stringify!($arg).to_lowercase(), $arg::oxid_type())),*];
+
+                let func = move |args: Vec<Data>| {
+                    let mut args = args.into_iter();
+                    $(let $arg = from_data::<$arg>(args.next().expect("argument count should have been checked by the parser"));)*
+
+                    Data::from(self($($arg),*).into())
+                };
+
+                HostFn::new(name, args_types, R::oxid_type(), Rc::new(func))
+            }
+        }
+    }
+}
+
+impl_into_host_fn!();
+impl_into_host_fn!(A);
+impl_into_host_fn!(A, B);
+impl_into_host_fn!(A, B, C);
+impl_into_host_fn!(A, B, C, D);
+impl_into_host_fn!(A, B, C, D, E);
+impl_into_host_fn!(A, B, C, D, E, G);
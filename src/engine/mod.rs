@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::builtin::BuiltinFn;
+use crate::data::Data;
+use crate::errors::{Error, ParseErr, RuntimeErr};
+use crate::interpreter::{Interpreter, Limits};
+use crate::memory::{Memory, Scope};
+use crate::parser::{Parser, TokenCollector};
+use crate::sandbox::Capabilities;
+use crate::statements::Statement;
+use crate::statements::module::{ModuleImport, ModuleRegistry, STD_SOURCE};
+use crate::tokenizer::tokenize;
+use crate::types::Type;
+
+pub use host::{HostFn, HostType, IntoHostFn};
+pub use object::{CustomType, Handle, HostClass, HostObject, HostTypeId, IntoHostMethod, TypeBuilder};
+pub use value::{Function, Value};
+
+pub mod host;
+pub mod object;
+pub mod value;
+
+/// Runs oxid code inside another application. Globals declared by one call stay around for
+/// the next, like the lines of the repl.
+///
+/// ```
+/// let mut engine = oxid::Engine::new();
+/// engine.set_global("name", "world").unwrap();
+/// engine.eval("let greeting = format(\"hello {}\", name);").unwrap();
+///
+/// let greeting: String = engine.get_global("greeting").unwrap().try_into().unwrap();
+/// assert_eq!(greeting, "hello world");
+/// ```
+pub struct Engine {
+    sim_memory: Memory<Type>,
+    memory: Memory<Data>,
+    modules: ModuleRegistry,
+    aliases: Scope<String>,
+    capabilities: Capabilities,
+    host_types: HashMap<String, Type>,
+    /// Returned by the `args` builtin
+    pub script_args: Vec<String>,
+    pub limits: Limits
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Engine {
+    /// An engine with the standard library and the default capabilities
+    pub fn new() -> Self {
+        Self::with_capabilities(Capabilities::default())
+    }
+
+    pub fn with_capabilities(capabilities: Capabilities) -> Self {
+        let mut sim_memory = Memory::new();
+        BuiltinFn::populate_sim_memory(&mut sim_memory, &capabilities);
+
+        let mut memory = Memory::new();
+        BuiltinFn::populate_memory(&mut memory, &capabilities);
+
+        let mut engine = Self {
+            sim_memory,
+            memory,
+            modules: ModuleRegistry::default(),
+            aliases: Scope::default(),
+            capabilities,
+            host_types: HashMap::new(),
+            script_args: vec![],
+            limits: Limits::default()
+        };
+
+        let std_import = ModuleImport::parse_std("<std>", STD_SOURCE, &mut engine.sim_memory, &mut engine.modules)
+            .expect("the embedded std should parse");
+        engine.run(&vec![Statement::ModuleImport(std_import)])
+            .expect("the embedded std should run");
+
+        engine
+    }
+
+    /// Runs `code` and returns the value of its last expression, the final semicolon can be
+    /// left out. If it fails, every global is left as it was before.
+    pub fn eval(&mut self, code: &str) -> Result<Value, Error> {
+        let code = code.trim();
+
+        match code.is_empty() || code.ends_with(';') || code.ends_with('}') {
+            true => self.eval_source("<eval>", code),
+            false => self.eval_source("<eval>", &format!("{};", code))
+        }
+    }
+
+    /// Runs a script, modules it imports are looked up next to it
+    pub fn run_file(&mut self, path: impl AsRef<Path>) -> Result<Value, Error> {
+        let filename = path.as_ref().display().to_string();
+        let buf = fs::read_to_string(&path)
+            .map_err(|err| Error::Io(format!("failed to read file `{}`: {}", filename, err)))?;
+
+        self.eval_source(&filename, buf.trim())
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        self.memory.scopes[0].get(name).cloned().map(Value::from)
+    }
+
+    /// Declares a global or replaces the value of one. Replacing it with a value of another
+    /// type fails with [`Error::TypeMismatch`], as functions already checked against the old
+    /// type could still use it.
+    pub fn set_global(&mut self, name: &str, value: impl Into<Value>) -> Result<(), Error> {
+        let data = Data::from(value.into());
+        let typ = data.get_type();
+
+        if let Some(old_type) = self.sim_memory.scopes[0].get(name) {
+            if *old_type != typ {
+                return Err(Error::TypeMismatch(old_type.get_name(), typ.get_name()));
+            }
+        }
+
+        self.sim_memory.scopes[0].insert(name.to_string(), typ);
+        self.memory.scopes[0].insert(name.to_string(), data);
+        Ok(())
+    }
+
+    /// Exposes a Rust closure to the program as a function, its signature is derived from the
+    /// argument and return types so calls to it are type checked like any other
+    ///
+    /// ```
+    /// let mut engine = oxid::Engine::new();
+    /// engine.register_fn("repeat", |s: String, n: f64| s.repeat(n as usize));
+    ///
+    /// assert_eq!(engine.eval("repeat(\"ab\", 3)").unwrap(), oxid::Value::from("ababab"));
+    /// assert!(engine.eval("repeat(3, \"ab\")").is_err());
+    /// ```
+    pub fn register_fn<Args>(&mut self, name: &str, func: impl IntoHostFn<Args>) {
+        let host_fn = func.into_host_fn(name);
+
+        self.sim_memory.scopes[0].insert(name.to_string(), host_fn.get_type());
+        self.memory.scopes[0].insert(name.to_string(), Data::HostFn(host_fn));
+    }
+
+    /// Lets scripts name a [`CustomType`] in type annotations, values of it can be passed in
+    /// with [`Handle`]s
+    pub fn register_type<T: CustomType>(&mut self) {
+        let host_type = Handle::<T>::oxid_type();
+        self.host_types.insert(T::NAME.to_string(), host_type);
+    }
+
+    fn eval_source(&mut self, filename: &str, buf: &str) -> Result<Value, Error> {
+        self.sim_memory.checkpoint();
+        self.memory.checkpoint();
+        self.modules.checkpoint();
+        self.aliases.checkpoint();
+
+        let res = self.parse(filename, buf)
+            .map_err(Error::Parse)
+            .and_then(|statements| self.exec(&statements).map_err(Error::Runtime));
+
+        // anything a failed input declared is forgotten, values assigned in place are kept
+        if res.is_ok() {
+            self.sim_memory.commit();
+            self.memory.commit();
+            self.modules.commit();
+            self.aliases.commit();
+        } else {
+            self.sim_memory.rollback();
+            self.memory.rollback();
+            self.modules.rollback();
+            self.aliases.rollback();
+        }
+
+        res.map(Value::from)
+    }
+
+    fn parse(&mut self, filename: &str, buf: &str) -> Result<Vec<Statement>, Vec<ParseErr>> {
+        let tokens = tokenize(filename, buf).map_err(|err| vec![err])?;
+
+        let mut parser = Parser::new(TokenCollector::new(&tokens), &mut self.sim_memory);
+        parser.modules = std::mem::take(&mut self.modules);
+        parser.aliases = std::mem::take(&mut self.aliases);
+        parser.capabilities = self.capabilities;
+        parser.host_types = self.host_types.clone();
+        let res = parser.parse_program();
+
+        self.modules = parser.modules;
+        self.aliases = parser.aliases;
+
+        res
+    }
+
+    fn interpreter<'a>(&'a mut self, statements: &'a Vec<Statement>) -> Interpreter<'a, 'a> {
+        let mut interpreter = Interpreter::new(statements, &mut self.memory, self.capabilities);
+        interpreter.script_args = self.script_args.clone();
+        interpreter.limits = self.limits;
+        interpreter
+    }
+
+    fn run(&mut self, statements: &Vec<Statement>) -> Result<(), RuntimeErr> {
+        self.interpreter(statements).run_program()
+    }
+
+    /// Like `run` but keeps the value of a trailing expression statement
+    fn exec(&mut self, statements: &Vec<Statement>) -> Result<Data, RuntimeErr> {
+        let mut interpreter = self.interpreter(statements);
+        let mut value = Data::TempNil;
+
+        for (i, statement) in statements.iter().enumerate() {
+            match statement {
+                Statement::Expr(expr) if i == statements.len() - 1 => value = expr.eval(&mut interpreter)?,
+                _ => statement.exec(&mut interpreter)?
+            }
+        }
+
+        Ok(value)
+    }
+}
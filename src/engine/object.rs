@@ -0,0 +1,343 @@
+use std::any::{Any, TypeId};
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use crate::data::Data;
+use crate::errors::Error;
+use crate::types::Type;
+use super::{HostFn, HostType, Value};
+
+/// A Rust type scripts can hold handles to, using the fields and methods it describes
+///
+/// ```
+/// use oxid::engine::{CustomType, Handle, TypeBuilder};
+///
+/// struct Counter {
+///     count: f64
+/// }
+///
+/// impl CustomType for Counter {
+///     const NAME: &'static str = "Counter";
+///
+///     fn build(builder: &mut TypeBuilder<Self>) {
+///         builder
+///             .getter("count", |counter: &Counter| counter.count)
+///             .method("add", |counter: &mut Counter, n: f64| counter.count += n);
+///     }
+/// }
+///
+/// let counter = Handle::new(Counter { count: 0.0 });
+///
+/// let mut engine = oxid::Engine::new();
+/// engine.register_type::<Counter>();
+/// engine.set_global("counter", counter.clone()).unwrap();
+/// engine.eval("fn twice(c: Counter) { c.add(1); c.add(1); } twice(counter);").unwrap();
+///
+/// assert_eq!(counter.borrow().count, 2.0);
+/// ```
+pub trait CustomType: Any + Sized {
+    /// What the type is called in scripts, in type annotations and error messages
+    const NAME: &'static str;
+
+    fn build(builder: &mut TypeBuilder<Self>);
+}
+
+/// Identifies a [`CustomType`] in [`Type::Host`], its fields are looked up in the registry
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HostTypeId {
+    pub name: &'static str,
+    type_id: TypeId
+}
+
+impl HostTypeId {
+    pub fn of<T: CustomType>() -> Self {
+        Self {
+            name: T::NAME,
+            type_id: TypeId::of::<T>()
+        }
+    }
+}
+
+/// Closures taking the type erased `RefCell<T>` of a [`HostObject`]
+type Getter = Rc<dyn Fn(&dyn Any) -> Data>;
+type Setter = Rc<dyn Fn(&dyn Any, Data)>;
+type MethodCall = Rc<dyn Fn(&dyn Any, Vec<Data>) -> Data>;
+
+struct HostField {
+    name: String,
+    field_type: Type,
+    get: Getter,
+    set: Option<Setter>
+}
+
+/// A method of a [`CustomType`], made by [`IntoHostMethod`]
+pub struct HostMethod {
+    name: String,
+    args_types: Vec<(String, Type)>,
+    return_type: Type,
+    call: MethodCall
+}
+
+impl HostMethod {
+    fn get_type(&self) -> Type {
+        Type::Fn { args_types: self.args_types.clone(), return_type: Box::new(self.return_type.clone()) }
+    }
+}
+
+/// The fields and methods of a [`CustomType`], built once per thread
+pub struct HostClass {
+    pub id: HostTypeId,
+    fields: Vec<HostField>,
+    methods: Vec<HostMethod>
+}
+
+thread_local! {
+    static CLASSES: RefCell<HashMap<TypeId, Rc<HostClass>>> = RefCell::new(HashMap::new());
+    /// Classes being built, so a type mentioning itself doesn't build forever
+    static BUILDING: RefCell<HashSet<TypeId>> = RefCell::new(HashSet::new());
+}
+
+impl HostClass {
+    /// Builds and registers the class of `T` unless that already happened
+    pub fn register<T: CustomType>() {
+        let type_id = TypeId::of::<T>();
+
+        let known = CLASSES.with_borrow(|classes| classes.contains_key(&type_id));
+        if known || !BUILDING.with_borrow_mut(|building| building.insert(type_id)) {
+            return;
+        }
+
+        let mut builder = TypeBuilder::<T> {
+            class: HostClass { id: HostTypeId::of::<T>(), fields: vec![], methods: vec![] },
+            _marker: PhantomData
+        };
+        T::build(&mut builder);
+
+        BUILDING.with_borrow_mut(|building| building.remove(&type_id));
+        CLASSES.with_borrow_mut(|classes| classes.insert(type_id, Rc::new(builder.class)));
+    }
+
+    pub fn lookup(id: &HostTypeId) -> Option<Rc<HostClass>> {
+        CLASSES.with_borrow(|classes| classes.get(&id.type_id).cloned())
+    }
+
+    /// Type of reading `name` from a value of this type, either a field or a method
+    pub fn member_type(&self, name: &str) -> Option<Type> {
+        let field_type = self.fields.iter()
+            .find(|field| field.name == name)
+            .map(|field| field.field_type.clone());
+
+        field_type.or_else(|| {
+            self.methods.iter().find(|method| method.name == name).map(HostMethod::get_type)
+        })
+    }
+
+    /// Type a field has to be assigned with, `None` when there is no setter
+    pub fn setter_type(&self, name: &str) -> Option<Type> {
+        self.fields.iter()
+            .find(|field| field.name == name && field.set.is_some())
+            .map(|field| field.field_type.clone())
+    }
+}
+
+impl fmt::Debug for HostClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HostClass({})", self.id.name)
+    }
+}
+
+/// Collects the fields and methods of a [`CustomType`] in [`CustomType::build`]
+pub struct TypeBuilder<T> {
+    class: HostClass,
+    _marker: PhantomData<T>
+}
+
+impl<T: CustomType> TypeBuilder<T> {
+    /// Lets scripts read `value.name`
+    pub fn getter<R: HostType>(&mut self, name: &str, get: impl Fn(&T) -> R + 'static) -> &mut Self {
+        self.class.fields.push(HostField {
+            name: name.to_string(),
+            field_type: R::oxid_type(),
+            get: Rc::new(move |value: &dyn Any| Data::from(get(&borrow::<T>(value).borrow()).into())),
+            set: None
+        });
+
+        self
+    }
+
+    /// Lets scripts assign `value.name = new_value`, the field needs a getter of the same type
+    pub fn setter<V: HostType>(&mut self, name: &str, set: impl Fn(&mut T, V) + 'static) -> &mut Self {
+        let field = self.class.fields.iter_mut()
+            .find(|field| field.name == name)
+            .unwrap_or_else(|| panic!("`{}.{}` needs a getter before its setter", T::NAME, name));
+
+        assert_eq!(field.field_type, V::oxid_type(), "the getter and setter of `{}.{}` should use the same type", T::NAME, name);
+
+        field.set = Some(Rc::new(move |value: &dyn Any, data: Data| {
+            set(&mut borrow::<T>(value).borrow_mut(), from_data(data))
+        }));
+
+        self
+    }
+
+    /// Lets scripts call `value.name(args)`, the closure gets the value as its first argument
+    pub fn method<Args>(&mut self, name: &str, method: impl IntoHostMethod<T, Args>) -> &mut Self {
+        self.class.methods.push(method.into_host_method(name));
+        self
+    }
+}
+
+fn borrow<T: 'static>(value: &dyn Any) -> &RefCell<T> {
+    value.downcast_ref().expect("host values should match their class")
+}
+
+/// Converts a value the parser already checked the type of
+fn from_data<T: HostType>(data: Data) -> T {
+    T::try_from(Value::from(data)).expect("values should have been type checked by the parser")
+}
+
+/// Closures that can be methods of a [`CustomType`] `T`, taking `&mut T` and then up to five
+/// [`HostType`] arguments
+pub trait IntoHostMethod<T, Args> {
+    #[doc(hidden)]
+    fn into_host_method(self, name: &str) -> HostMethod;
+}
+
+macro_rules! impl_into_host_method {
+    ($($arg:ident),*) => {
+        impl<T, F, R, $($arg),*> IntoHostMethod<T, ($($arg,)*)> for F
+        where
+            T: CustomType,
+            F: Fn(&mut T, $($arg),*) -> R + 'static,
+            R: HostType,
+            $($arg: HostType),*
+        {
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn into_host_method(self, name: &str) -> HostMethod {
+                let args_types = vec![$((stringify!($arg).to_lowercase(), $arg::oxid_type())),*];
+
+                let call = move |value: &dyn Any, args: Vec<Data>| {
+                    let mut args = args.into_iter();
+                    $(let $arg = from_data::<$arg>(args.next().expect("argument count should have been checked by the parser"));)*
+
+                    let res = self(&mut borrow::<T>(value).borrow_mut(), $($arg),*);
+                    Data::from(res.into())
+                };
+
+                HostMethod {
+                    name: name.to_string(),
+                    args_types,
+                    return_type: R::oxid_type(),
+                    call: Rc::new(call)
+                }
+            }
+        }
+    }
+}
+
+impl_into_host_method!();
+impl_into_host_method!(A);
+impl_into_host_method!(A, B);
+impl_into_host_method!(A, B, C);
+impl_into_host_method!(A, B, C, D);
+impl_into_host_method!(A, B, C, D, E);
+
+/// A value of a [`CustomType`] shared between the host and scripts, changes made by either
+/// are seen by both
+pub struct Handle<T>(Rc<RefCell<T>>);
+
+impl<T> Handle<T> {
+    pub fn new(value: T) -> Self {
+        Self(Rc::new(RefCell::new(value)))
+    }
+
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.0.borrow()
+    }
+
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        self.0.borrow_mut()
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self(Rc::clone(&self.0))
+    }
+}
+
+impl<T: CustomType> HostType for Handle<T> {
+    fn oxid_type() -> Type {
+        HostClass::register::<T>();
+        Type::Host(HostTypeId::of::<T>())
+    }
+}
+
+impl<T: CustomType> From<Handle<T>> for Value {
+    fn from(handle: Handle<T>) -> Self {
+        HostClass::register::<T>();
+        let class = HostClass::lookup(&HostTypeId::of::<T>()).expect("the class was just registered");
+
+        Value::Host(HostObject { class, value: handle.0 })
+    }
+}
+
+impl<T: CustomType> TryFrom<Value> for Handle<T> {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Host(object) if object.class.id == HostTypeId::of::<T>() => Ok(Handle(
+                object.value.downcast().expect("host values should match their class")
+            )),
+            _ => Err(Error::TypeMismatch(T::NAME.to_string(), value.type_name().to_string()))
+        }
+    }
+}
+
+/// A [`Handle`] as it's stored in [`Data`], with its type erased
+#[derive(Clone)]
+pub struct HostObject {
+    pub class: Rc<HostClass>,
+    value: Rc<dyn Any>
+}
+
+impl HostObject {
+    /// Reads a field, or binds a method to the object so it can be called
+    pub fn get(&self, name: &str) -> Data {
+        if let Some(field) = self.class.fields.iter().find(|field| field.name == name) {
+            return (field.get)(self.value.as_ref());
+        }
+
+        let method = self.class.methods.iter()
+            .find(|method| method.name == name)
+            .expect("should have already been caught in parsing");
+
+        let (call, value) = (Rc::clone(&method.call), Rc::clone(&self.value));
+        let func = move |args: Vec<Data>| call(value.as_ref(), args);
+
+        Data::HostFn(HostFn::new(name, method.args_types.clone(), method.return_type.clone(), Rc::new(func)))
+    }
+
+    pub fn set(&self, name: &str, data: Data) {
+        let set = self.class.fields.iter()
+            .find(|field| field.name == name)
+            .and_then(|field| field.set.clone())
+            .expect("should have already been caught in parsing");
+
+        set(self.value.as_ref(), data)
+    }
+
+    pub fn ptr_eq(&self, other: &HostObject) -> bool {
+        Rc::ptr_eq(&self.value, &other.value)
+    }
+}
+
+impl fmt::Debug for HostObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HostObject({})", self.class.id.name)
+    }
+}
@@ -0,0 +1,268 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::data::Data;
+use crate::errors::Error;
+use super::HostObject;
+
+/// A value passed between oxid and the application embedding it, see [`crate::Engine`]
+#[derive(Debug, Clone)]
+pub enum Value {
+    /// What statements and functions without a result evaluate to
+    Nil,
+    Number(f64),
+    String(String),
+    Bool(bool),
+    Option(Option<Box<Value>>),
+    Result(Result<Box<Value>, String>),
+    List(Vec<Value>),
+    Record(Vec<(String, Value)>),
+    Fn(Function),
+    /// A [`super::Handle`] to a value of a host type
+    Host(HostObject)
+}
+
+/// A function of the program, only good for passing back into it
+#[derive(Debug, Clone)]
+pub struct Function(Data);
+
+impl Function {
+    pub fn name(&self) -> String {
+        match &self.0 {
+            Data::Fn(fn_decl) => fn_decl.name.to_string(),
+            Data::BuiltinFn(builtin_fn) => builtin_fn.get_name().to_string(),
+            Data::HostFn(host_fn) => host_fn.name.to_string(),
+            Data::VmFn(function) => function.name.to_string(),
+            _ => unreachable!("functions only hold fn data")
+        }
+    }
+
+    /// Whether both are the same function, not just two functions sharing a name
+    pub fn ptr_eq(&self, other: &Function) -> bool {
+        match (&self.0, &other.0) {
+            (Data::Fn(a), Data::Fn(b)) => Rc::ptr_eq(a, b),
+            (Data::BuiltinFn(a), Data::BuiltinFn(b)) => a == b,
+            (Data::HostFn(a), Data::HostFn(b)) => a.ptr_eq(b),
+            (Data::VmFn(a), Data::VmFn(b)) => Rc::ptr_eq(a, b),
+            _ => false
+        }
+    }
+}
+
+impl Value {
+    /// Name of the kind of value, used when a conversion fails
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Nil => "Nil",
+            Value::Number(_) => "Number",
+            Value::String(_) => "String",
+            Value::Bool(_) => "Bool",
+            Value::Option(_) => "Option",
+            Value::Result(_) => "Result",
+            Value::List(_) => "List",
+            Value::Record(_) => "Record",
+            Value::Fn(_) => "Fn",
+            Value::Host(object) => object.class.id.name
+        }
+    }
+
+    fn mismatch(&self, expected: &str) -> Error {
+        Error::TypeMismatch(expected.to_string(), self.type_name().to_string())
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => true,
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Option(a), Value::Option(b)) => a == b,
+            (Value::Result(a), Value::Result(b)) => a == b,
+            (Value::List(a), Value::List(b)) => a == b,
+            (Value::Record(a), Value::Record(b)) => a == b,
+            (Value::Fn(a), Value::Fn(b)) => a.ptr_eq(b),
+            (Value::Host(a), Value::Host(b)) => a.ptr_eq(b),
+            _ => false
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Data::from(self.clone()))
+    }
+}
+
+impl From<Data> for Value {
+    fn from(data: Data) -> Self {
+        match data {
+            Data::String(val) => Value::String(Rc::unwrap_or_clone(val)),
+            Data::Number(val) => Value::Number(val),
+            Data::Bool(val) => Value::Bool(val),
+            Data::Option(val) => Value::Option(val.map(|val| Box::new(Value::from(*val)))),
+            Data::Result(val) => Value::Result(val.map(|val| Box::new(Value::from(*val)))),
+            Data::List(items) => Value::List(Rc::unwrap_or_clone(items).into_iter().map(Value::from).collect()),
+            Data::Record(fields) => Value::Record(
+                Rc::unwrap_or_clone(fields).into_iter().map(|(name, field)| (name, Value::from(field))).collect()
+            ),
+            Data::Fn(_) | Data::BuiltinFn(_) | Data::HostFn(_) | Data::VmFn(_) => Value::Fn(Function(data)),
+            Data::Host(object) => Value::Host(object),
+            Data::TempNil => Value::Nil
+        }
+    }
+}
+
+impl From<Value> for Data {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Nil => Data::TempNil,
+            Value::Number(val) => Data::Number(val),
+            Value::String(val) => Data::String(Rc::new(val)),
+            Value::Bool(val) => Data::Bool(val),
+            Value::Option(val) => Data::Option(val.map(|val| Box::new(Data::from(*val)))),
+            Value::Result(val) => Data::Result(val.map(|val| Box::new(Data::from(*val)))),
+            Value::List(items) => Data::List(Rc::new(items.into_iter().map(Data::from).collect())),
+            Value::Record(fields) => Data::Record(Rc::new(
+                fields.into_iter().map(|(name, field)| (name, Data::from(field))).collect()
+            )),
+            Value::Fn(Function(data)) => data,
+            Value::Host(object) => Data::Host(object)
+        }
+    }
+}
+
+impl From<()> for Value {
+    fn from(_: ()) -> Self {
+        Value::Nil
+    }
+}
+
+impl From<f64> for Value {
+    fn from(val: f64) -> Self {
+        Value::Number(val)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(val: i32) -> Self {
+        Value::Number(val as f64)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(val: bool) -> Self {
+        Value::Bool(val)
+    }
+}
+
+impl From<String> for Value {
+    fn from(val: String) -> Self {
+        Value::String(val)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(val: &str) -> Self {
+        Value::String(val.to_string())
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(val: Option<T>) -> Self {
+        Value::Option(val.map(|val| Box::new(val.into())))
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(items: Vec<T>) -> Self {
+        Value::List(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Value>> From<Result<T, String>> for Value {
+    fn from(val: Result<T, String>) -> Self {
+        Value::Result(val.map(|val| Box::new(val.into())))
+    }
+}
+
+impl TryFrom<Value> for () {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Nil => Ok(()),
+            _ => Err(value.mismatch("Nil"))
+        }
+    }
+}
+
+impl TryFrom<Value> for f64 {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Number(val) => Ok(val),
+            _ => Err(value.mismatch("Number"))
+        }
+    }
+}
+
+impl TryFrom<Value> for bool {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Bool(val) => Ok(val),
+            _ => Err(value.mismatch("Bool"))
+        }
+    }
+}
+
+impl TryFrom<Value> for String {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::String(val) => Ok(val),
+            _ => Err(value.mismatch("String"))
+        }
+    }
+}
+
+impl<T: TryFrom<Value, Error = Error>> TryFrom<Value> for Option<T> {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Option(val) => val.map(|val| T::try_from(*val)).transpose(),
+            _ => Err(value.mismatch("Option"))
+        }
+    }
+}
+
+impl<T: TryFrom<Value, Error = Error>> TryFrom<Value> for Vec<T> {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::List(items) => items.into_iter().map(T::try_from).collect(),
+            _ => Err(value.mismatch("List"))
+        }
+    }
+}
+
+impl<T: TryFrom<Value, Error = Error>> TryFrom<Value> for Result<T, String> {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Result(val) => match val {
+                Ok(val) => T::try_from(*val).map(Ok),
+                Err(err) => Ok(Err(err))
+            },
+            _ => Err(value.mismatch("Result"))
+        }
+    }
+}
@@ -1,4 +1,7 @@
+use std::fmt;
+
 use crate::operations::Operation;
+use crate::sandbox::Capability;
 use crate::tokenizer::token::{Token, TokenPos};
 
 #[derive(Debug, Clone)]
@@ -9,12 +12,13 @@ pub struct ParseErr {
 
 impl ParseErr {
     pub fn report(&self) -> ! {
-        panic!(
-            "{}: {} ({})",
-            self.token_pos.filename,
-            self.err_kind.err_msg(),
-            self.token_pos
-        )
+        panic!("{}", self)
+    }
+}
+
+impl fmt::Display for ParseErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.token_pos.filename, self.token_pos, self.err_kind.err_msg())
     }
 }
 
@@ -26,39 +30,136 @@ pub enum ParseErrKind {
     IncompatiableOperation(Operation, String, String),
     UnmatchedDelimiter(char),
     InvalidPathUse(String),
-    NotCallable()
+    NotCallable(),
+    InvalidArguments(String, Vec<String>),
+    UnknownEscape(char),
+    InvalidUnicodeEscape(),
+    UnterminatedBlockComment(),
+    DanglingDocComment(),
+    ModuleNotFound(String),
+    PrivateItem(String),
+    ImportCycle(Vec<String>),
+    NotIndexable(String, String),
+    MixedListItems(String, String),
+    UnknownRecordField(String, String),
+    CapabilityDenied(String, Capability),
+    ReadOnlyField(String, String),
+    AssignTypeMismatch(String, String),
+    /// A `format` template with a different number of placeholders than arguments
+    FormatArgCount(usize, usize)
 }
 
 impl ParseErrKind {
     pub fn to_err(self, token_pos: TokenPos) -> ParseErr {
-        let ok = ParseErr {
+        ParseErr {
             err_kind: self,
             token_pos
-        };
-        ok.report();
+        }
     }
 
     pub fn from_token(self, token: &Token) -> ParseErr {
-        let ok = ParseErr {
-            err_kind: self,
-            token_pos: token.token_pos.clone()
-        };
-        ok.report()
+        self.to_err(token.token_pos.clone())
     }
 
     fn err_msg(&self) -> String {
         match self {
             ParseErrKind::UnexpectedChar(c) => format!("unexpected character `{}`", c),
             ParseErrKind::UnexpectedToken(got, expected) => format!("expected {}, got {}", expected, got),
-            ParseErrKind::UnknownField() => format!("unknown field `i cant be fucked lol`"),
+            ParseErrKind::UnknownField() => "unknown field `i cant be fucked lol`".to_string(),
             ParseErrKind::IncompatiableOperation(operation, lhs, rhs) => format!("incompatiable {:?} operation on {} and {}", operation, lhs, rhs),
             ParseErrKind::UnmatchedDelimiter(c) => format!("unmatched delimiter `{}`", c),
             ParseErrKind::InvalidPathUse(expr_type) => format!("cannot use {} as a path", expr_type),
-            ParseErrKind::NotCallable() => format!("some expr is not callable icbf")
+            ParseErrKind::NotCallable() => "some expr is not callable icbf".to_string(),
+            ParseErrKind::InvalidArguments(fn_name, args) => format!("invalid arguments ({}) passed to `{}`", args.join(", "), fn_name),
+            ParseErrKind::UnknownEscape(c) => format!("unknown character escape `\\{}`", c),
+            ParseErrKind::InvalidUnicodeEscape() => "invalid unicode escape, expected `\\u{...}` with up to 6 hex digits".to_string(),
+            ParseErrKind::UnterminatedBlockComment() => "unterminated block comment".to_string(),
+            ParseErrKind::DanglingDocComment() => "doc comments must be followed by a `fn` or `let`".to_string(),
+            ParseErrKind::ModuleNotFound(path) => format!("module file `{}` not found", path),
+            ParseErrKind::PrivateItem(path) => format!("`{}` is private", path),
+            ParseErrKind::ImportCycle(chain) => format!("circular module import: {}", chain.join(" -> ")),
+            ParseErrKind::NotIndexable(value_type, index_type) => format!("cannot index {} with {}", value_type, index_type),
+            ParseErrKind::MixedListItems(expected, got) => format!("list items must all be {}, got {}", expected, got),
+            ParseErrKind::UnknownRecordField(record_type, field) => format!("{} has no field `{}`", record_type, field),
+            ParseErrKind::CapabilityDenied(name, capability) => format!("`{}` needs the {} capability", name, capability),
+            ParseErrKind::ReadOnlyField(value_type, field) => format!("field `{}` of {} can't be assigned to", field, value_type),
+            ParseErrKind::AssignTypeMismatch(expected, got) => format!("cannot assign {} to a field of type {}", got, expected),
+            ParseErrKind::FormatArgCount(placeholders, args) => format_arg_count_msg(*placeholders, *args)
         }
     }
 }
 
+/// An error while running a program, unwinding the interpreter back to whoever started it
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeErr {
+    /// `exit(code)` was called, not a failure in itself but it unwinds the same way
+    Exit(i32),
+    IndexOutOfBounds(usize, usize),
+    /// A string or list was indexed with a negative or fractional number
+    InvalidIndex(f64),
+    /// `exit` was called with a fractional, non-finite or out of range code
+    InvalidExitCode(f64),
+    /// Same as [`ParseErrKind::FormatArgCount`] for templates that aren't string literals
+    FormatArgCount(usize, usize),
+    AssertionFailed(Option<String>),
+    UnwrapFailed(String),
+    CapabilityDenied(String, Capability),
+    StepLimitExceeded(u64),
+    CallDepthExceeded(usize),
+    MemoryLimitExceeded(usize)
+}
+
+impl fmt::Display for RuntimeErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuntimeErr::Exit(code) => write!(f, "exited with code {}", code),
+            RuntimeErr::IndexOutOfBounds(index, len) => write!(f, "index {} out of bounds for length {}", index, len),
+            RuntimeErr::InvalidIndex(index) => write!(f, "index {} is not a whole number of at least 0", index),
+            RuntimeErr::InvalidExitCode(code) => write!(f, "exit code {} is not a 32 bit whole number", code),
+            RuntimeErr::FormatArgCount(placeholders, args) => write!(f, "{}", format_arg_count_msg(*placeholders, *args)),
+            RuntimeErr::AssertionFailed(None) => write!(f, "assertion failed"),
+            RuntimeErr::AssertionFailed(Some(msg)) => write!(f, "assertion failed: {}", msg),
+            RuntimeErr::UnwrapFailed(val) => write!(f, "called `unwrap` on {}", val),
+            RuntimeErr::CapabilityDenied(name, capability) => write!(f, "`{}` needs the {} capability", name, capability),
+            RuntimeErr::StepLimitExceeded(max) => write!(f, "step limit of {} exceeded", max),
+            RuntimeErr::CallDepthExceeded(max) => write!(f, "call depth limit of {} exceeded", max),
+            RuntimeErr::MemoryLimitExceeded(max) => write!(f, "memory limit of {} bytes exceeded", max)
+        }
+    }
+}
+
+/// Anything that can go wrong running code through an [`crate::Engine`]
+#[derive(Debug, Clone)]
+pub enum Error {
+    Io(String),
+    /// Every error found while parsing and type checking the program
+    Parse(Vec<ParseErr>),
+    Runtime(RuntimeErr),
+    /// A value couldn't be converted to the Rust type asked for, holds the expected and actual type
+    TypeMismatch(String, String)
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{}", err),
+            Error::Parse(errs) => {
+                let errs: Vec<_> = errs.iter().map(|err| err.to_string()).collect();
+                write!(f, "{}", errs.join("\n"))
+            },
+            Error::Runtime(err) => write!(f, "{}", err),
+            Error::TypeMismatch(expected, got) => write!(f, "expected {}, got {}", expected, got)
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
 pub fn map_err_token<T>(res: Result<T, ParseErrKind>, token: &Token) -> Result<T, ParseErr> {
     res.map_err(|err_kind| err_kind.from_token(token))
 }
+
+fn format_arg_count_msg(placeholders: usize, args: usize) -> String {
+    let plural = |n: usize| if n == 1 { "" } else { "s" };
+    format!("`format` template has {} placeholder{} but got {} argument{}", placeholders, plural(placeholders), args, plural(args))
+}
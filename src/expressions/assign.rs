@@ -1,5 +1,5 @@
 use crate::data::Data;
-use crate::errors::{ParseErrKind, ParseErr, map_err_token};
+use crate::errors::{ParseErrKind, ParseErr, RuntimeErr};
 use crate::expressions::{Expr, Evaluable};
 use crate::interpreter::Interpreter;
 use crate::operations::Operation;
@@ -15,9 +15,9 @@ pub enum AssignOp {
 
 #[derive(Debug, Clone)]
 pub struct ExprAssign {
-    op: AssignOp,
-    lhs: Box<Expr>,
-    rhs: Box<Expr>
+    pub op: AssignOp,
+    pub lhs: Box<Expr>,
+    pub rhs: Box<Expr>
 }
 
 impl ExprAssign {
@@ -32,41 +32,67 @@ impl ExprAssign {
 
 impl Evaluable for ExprAssign {
     fn type_check(&self, parser: &Parser) -> Type {
-        let mangled = self.lhs.mangle_path().unwrap();
-
         let value = self.rhs.type_check(parser);
-        let old = parser.sim_memory.get(&mangled).unwrap();
 
         match self.op {
-            AssignOp::Eq => self.rhs.type_check(parser),
-            AssignOp::AddEq => Operation::Add.typ(old, &value).unwrap()
+            AssignOp::Eq => value,
+            AssignOp::AddEq => Operation::Add.typ(&self.lhs.type_check(parser), &value).unwrap()
         }
     }
 
-    fn eval(&self, interpreter: &mut Interpreter) -> Data {
-        let mangled = self.lhs.mangle_path().unwrap();
+    fn eval(&self, interpreter: &mut Interpreter) -> Result<Data, RuntimeErr> {
+        if let Expr::Member(member_expr) = self.lhs.as_ref() {
+            let value = self.rhs.eval(interpreter)?;
+            let data = match self.op {
+                AssignOp::Eq => value,
+                AssignOp::AddEq => Operation::Add.op(&member_expr.eval(interpreter)?, &value)
+            };
 
-        let value = self.rhs.eval(interpreter);
-        let old = interpreter.memory.get(&mangled).unwrap();
+            member_expr.assign(interpreter, data.clone())?;
+            return Ok(data);
+        }
 
-        let data = match self.op {
-            AssignOp::Eq => value,
-            AssignOp::AddEq => Operation::Add.op(old, &value)
+        let field_expr = match self.lhs.as_ref() {
+            Expr::Field(field_expr) => field_expr,
+            _ => unreachable!("only paths and members can be assigned to")
         };
 
-        interpreter.memory.assign(mangled, data.clone());
+        let value = self.rhs.eval(interpreter)?;
 
-        data
+        match self.op {
+            AssignOp::Eq => {
+                interpreter.store_memory(&value, |memory| field_expr.lookup(memory).map_or(0, Data::heap_size))?;
+                field_expr.assign(interpreter.memory, value.clone());
+                Ok(value)
+            },
+            AssignOp::AddEq => {
+                // added in place so appending to a string that isn't shared doesn't copy it,
+                // which grows the variable by the size of the value
+                interpreter.store_memory(&value, |_| 0)?;
+                let data = field_expr.lookup_mut(interpreter.memory).unwrap();
+                Operation::Add.op_assign(data, &value);
+                Ok(data.clone())
+            }
+        }
     }
 }
 
 pub fn parse(parser: &mut Parser, first_token: &Token, expr: Expr, assign_op: AssignOp) -> Result<Expr, ParseErr> {
-    // check if lhs is able to be used as a path
-    expr.mangle_path()
-        .ok_or_else(||
-            ParseErrKind::InvalidPathUse(format!("{:?}", expr))
-            .from_token(first_token)
-        )?;
+    // check if lhs is able to be used as a path, or is a field with a setter
+    let setter_type = match &expr {
+        Expr::Member(member_expr) => Some(member_expr.setter_type(parser).ok_or_else(||
+            ParseErrKind::ReadOnlyField(member_expr.value.type_check(parser).get_name(), member_expr.field_name.to_string())
+                .from_token(first_token)
+        )?),
+        _ => {
+            expr.mangle_path()
+                .ok_or_else(||
+                    ParseErrKind::InvalidPathUse(format!("{:?}", expr))
+                    .from_token(first_token)
+                )?;
+            None
+        }
+    };
 
     let expr_type = expr.type_check(parser);
 
@@ -83,13 +109,27 @@ pub fn parse(parser: &mut Parser, first_token: &Token, expr: Expr, assign_op: As
         return Err(
             ParseErrKind::IncompatiableOperation(
                 Operation::Add,
-                expr_type.get_name().unwrap(),
-                rhs_type.get_name().unwrap()
+                expr_type.get_name(),
+                rhs_type.get_name()
             )
             .from_token(first_token)
         )
     }
 
+    if let Some(setter_type) = setter_type {
+        let assigned_type = match assign_op {
+            AssignOp::Eq => rhs_type,
+            AssignOp::AddEq => Operation::Add.typ(&expr_type, &rhs_type).unwrap()
+        };
+
+        if assigned_type != setter_type {
+            return Err(
+                ParseErrKind::AssignTypeMismatch(setter_type.get_name(), assigned_type.get_name())
+                    .from_token(first_token)
+            )
+        }
+    }
+
     let expr_assign = ExprAssign::new(assign_op, Box::new(expr), Box::new(rhs));
 
     Ok(Expr::Assign(expr_assign))
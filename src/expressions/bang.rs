@@ -1,6 +1,6 @@
-use crate::errors::ParseErrKind;
 use crate::expressions::{Evaluable, Expr};
 use crate::data::Data;
+use crate::errors::RuntimeErr;
 use crate::interpreter::Interpreter;
 use crate::parser::Parser;
 use crate::types::Type;
@@ -12,8 +12,8 @@ pub enum UnaryOp {
 
 #[derive(Debug, Clone)]
 pub struct ExprUnary {
-    op: UnaryOp,
-    value: Box<Expr>
+    pub op: UnaryOp,
+    pub value: Box<Expr>
 }
 
 impl Evaluable for ExprUnary {
@@ -21,7 +21,7 @@ impl Evaluable for ExprUnary {
         unimplemented!()
     }
 
-    fn eval(&self, _interpreter: &mut Interpreter) -> Data {
+    fn eval(&self, _interpreter: &mut Interpreter) -> Result<Data, RuntimeErr> {
         unimplemented!();
     }
 }
@@ -1,7 +1,7 @@
 use crate::operations::Operation;
 use crate::parser::Parser;
 use crate::types::Type;
-use crate::errors::{ParseErrKind, ParseErr, map_err_token};
+use crate::errors::{ParseErrKind, ParseErr, RuntimeErr};
 use crate::data::Data;
 use crate::interpreter::Interpreter;
 use crate::tokenizer::token::Token;
@@ -10,9 +10,9 @@ use super::{Expr, Evaluable};
 
 #[derive(Debug, Clone)]
 pub struct ExprBinary {
-    operation: Operation,
-    lhs: Box<Expr>,
-    rhs: Box<Expr>
+    pub operation: Operation,
+    pub lhs: Box<Expr>,
+    pub rhs: Box<Expr>
 }
 
 impl ExprBinary {
@@ -33,11 +33,11 @@ impl Evaluable for ExprBinary {
         self.operation.typ(&lhs, &rhs).unwrap()
     }
 
-    fn eval(&self, interpreter: &mut Interpreter) -> Data {
-        let lhs = self.lhs.eval(interpreter);
-        let rhs = self.rhs.eval(interpreter);
+    fn eval(&self, interpreter: &mut Interpreter) -> Result<Data, RuntimeErr> {
+        let lhs = self.lhs.eval(interpreter)?;
+        let rhs = self.rhs.eval(interpreter)?;
 
-        self.operation.op(&lhs, &rhs)
+        Ok(self.operation.op(&lhs, &rhs))
     }
 }
 
@@ -50,7 +50,7 @@ pub fn parse(parser: &mut Parser, first_token: &Token, expr: Expr, operation: Op
 
     operation.typ(&expr_type, &rhs_type)
         .ok_or_else(|| 
-            ParseErrKind::IncompatiableOperation(operation, expr_type.get_name().unwrap(), rhs_type.get_name().unwrap())
+            ParseErrKind::IncompatiableOperation(operation, expr_type.get_name(), rhs_type.get_name())
                 .from_token(first_token)
         )?;
 
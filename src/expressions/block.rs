@@ -1,6 +1,6 @@
 use crate::interpreter::Interpreter;
 use crate::parser::Parser;
-use crate::errors::{ParseErr, ParseErrKind};
+use crate::errors::{ParseErr, RuntimeErr};
 use crate::expressions::{Expr, Evaluable};
 use crate::data::Data;
 use crate::statements::Statement;
@@ -53,27 +53,27 @@ impl Evaluable for ExprBlock {
         }
     }
 
-    fn eval(&self, interpreter: &mut Interpreter) -> Data {
+    fn eval(&self, interpreter: &mut Interpreter) -> Result<Data, RuntimeErr> {
         let mut res = None;
 
         for (i, statement) in self.body.iter().enumerate() {
             match statement {
                 Statement::Expr(expr) => {
                     if i == self.body.len() - 1 {
-                        res = Some(expr.eval(interpreter));
+                        res = Some(expr.eval(interpreter)?);
                     } else {
-                        expr.eval(interpreter);
+                        expr.eval(interpreter)?;
                     }
                 },
-                _ => statement.exec(interpreter)
+                _ => statement.exec(interpreter)?
             }
         }
 
-        res.unwrap_or(Data::TempNil)
+        Ok(res.unwrap_or(Data::TempNil))
     }
 }
 
 pub fn parse(parser: &mut Parser) -> Result<Expr, ParseErr> {
     ExprBlock::parse_block_statements(parser)
-        .map(|block_expr| Expr::Block(block_expr))
+        .map(Expr::Block)
 }
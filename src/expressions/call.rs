@@ -1,5 +1,6 @@
-use crate::data::Data;
-use crate::errors::{ParseErrKind, ParseErr};
+use crate::builtin::{format, BuiltinFn};
+use crate::data::{Data, ExprLiteral};
+use crate::errors::{ParseErrKind, ParseErr, RuntimeErr};
 use crate::expressions::{Expr, Evaluable};
 use crate::helpers::destructive_loop;
 use crate::interpreter::Interpreter;
@@ -9,12 +10,12 @@ use crate::types::Type;
 
 #[derive(Debug, Clone)]
 pub struct ExprCall {
-    path: Box<Expr>,
-    args: Vec<Box<Expr>>
+    pub path: Box<Expr>,
+    pub args: Vec<Expr>
 }
 
 impl ExprCall {
-    pub fn new(path: Box<Expr>, args: Vec<Box<Expr>>) -> Self {
+    pub fn new(path: Box<Expr>, args: Vec<Expr>) -> Self {
         Self {
             path,
             args
@@ -24,50 +25,44 @@ impl ExprCall {
 
 impl Evaluable for ExprCall {
     fn type_check(&self, parser: &Parser) -> Type {
-        let _mangled_path = self.path.mangle_path().unwrap();
-
         let fn_type = self.path.type_check(parser);
         let args: Vec<_> = self.args.iter()
             .map(|arg_expr| arg_expr.type_check(parser))
             .collect();
 
         if let Type::Fn { args_types: _, return_type } = fn_type {
-            // TODO: check for that args types and args have same type
-
             return *return_type
         }
 
         if let Type::BuiltinFn(builtin) = fn_type {
-            return builtin.type_check(args);
+            return builtin.type_check(args).expect("should have already been caught in parsing");
         }
 
         unreachable!("should have already been caught in parsing");
     }
 
-    fn eval(&self, interpreter: &mut Interpreter) -> Data {
-        let _mangled_path = self.path.mangle_path().unwrap();
-
-        let fn_data = self.path.eval(interpreter);
-        let args: Vec<_> = self.args.iter()
+    fn eval(&self, interpreter: &mut Interpreter) -> Result<Data, RuntimeErr> {
+        let fn_data = self.path.eval(interpreter)?;
+        let args = self.args.iter()
             .map(|arg_expr| arg_expr.eval(interpreter))
-            .collect();
+            .collect::<Result<Vec<_>, _>>()?;
 
         if let Data::Fn(fn_decl) = fn_data {
-            interpreter.memory.push_scope();
-
-            for (i, arg_data) in args.iter().enumerate() {
-                let arg_type = &fn_decl.args[i];
-                interpreter.memory.insert(arg_type.0.to_string(), arg_data.clone());
-            }
+            return interpreter.call_fn(&fn_decl, args);
+        }
 
-            let res = fn_decl.body.eval(interpreter);
-            interpreter.memory.pop_scope();
+        if let Data::BuiltinFn(builtin_fn) = fn_data {
+            return builtin_fn.eval(args, interpreter);
+        }
 
-            return res;
+        if let Data::VmFn(function) = fn_data {
+            return crate::vm::call(interpreter, &function, args);
         }
 
-        if let Data::BuiltinFn(builtin_fn) = fn_data {
-            return builtin_fn.eval(args);
+        if let Data::HostFn(host_fn) = fn_data {
+            let data = host_fn.call(args);
+            interpreter.check_memory(&data)?;
+            return Ok(data);
         }
 
         unreachable!();
@@ -79,16 +74,15 @@ pub fn parse(parser: &mut Parser, _first_token: &Token, expr: Expr) -> Result<Ex
 
     destructive_loop!({
         let next_token = parser.collector.next();
-        match &next_token.token {
-            TokenType::RightParen => break,
-            _ => ()
+        if let TokenType::RightParen = next_token.token {
+            break
         }
 
         // TODO: prob explain to the user that it expects a rightparent too but current error
         // handler doesn't support that so ill fix it in post
         let arg_expr = Expr::parse_expr(parser, next_token)?;
 
-        args.push(Box::new(arg_expr));
+        args.push(arg_expr);
 
         let next_token = parser.collector.next();
         match &next_token.token {
@@ -99,11 +93,38 @@ pub fn parse(parser: &mut Parser, _first_token: &Token, expr: Expr) -> Result<Ex
     });
 
     let expr_type = expr.type_check(parser);
-
-    match expr_type {
-        Type::Fn { args_types: _, return_type: _ } => (),
-        Type::BuiltinFn(_) => (),
+    let arg_types: Vec<_> = args.iter()
+        .map(|arg_expr| arg_expr.type_check(parser))
+        .collect();
+
+    let args_are_valid = match &expr_type {
+        Type::Fn { args_types, return_type: _ } => {
+            args_types.len() == arg_types.len() &&
+                args_types.iter().zip(arg_types.iter()).all(|((_, expected), got)| expected == got)
+        },
+        Type::BuiltinFn(builtin_fn) => builtin_fn.type_check(arg_types.clone()).is_some(),
         _ => return Err(ParseErrKind::NotCallable().from_token(_first_token))
+    };
+
+    if !args_are_valid {
+        let fn_name = match &expr {
+            Expr::Member(member_expr) => member_expr.field_name.to_string(),
+            // calls like `fns[0](x)` have no name to show
+            _ => expr.mangle_path().unwrap_or_else(|| String::from("<expression>"))
+        };
+        let arg_names = arg_types.iter()
+            .map(|arg_type| arg_type.get_name())
+            .collect();
+
+        return Err(ParseErrKind::InvalidArguments(fn_name, arg_names).from_token(_first_token));
+    }
+
+    // other templates are only known, and checked, at runtime
+    if let (Type::BuiltinFn(BuiltinFn::Format), Some(Expr::Literal(ExprLiteral { data: Data::String(template) }))) = (&expr_type, args.first()) {
+        let placeholders = format::placeholder_count(template);
+        if placeholders != args.len() - 1 {
+            return Err(ParseErrKind::FormatArgCount(placeholders, args.len() - 1).from_token(_first_token));
+        }
     }
 
     Ok(Expr::Call(ExprCall::new(Box::new(expr), args)))
@@ -1,6 +1,6 @@
 use crate::interpreter::Interpreter;
 use crate::parser::Parser;
-use crate::errors::{ParseErr, ParseErrKind};
+use crate::errors::{ParseErr, RuntimeErr};
 use crate::expressions::{Expr, Evaluable, ExprBlock};
 use crate::data::Data;
 use crate::tokenizer::{token::Token, token_type::TokenType};
@@ -31,29 +31,22 @@ impl Evaluable for ExprFor {
     }
 
 
-    fn eval(&self, interpreter: &mut Interpreter) -> Data {
-        let start_i = self.start_i.eval(interpreter);
-        let end_i = self.end_i.eval(interpreter);
-
-        match start_i {
-            Data::Number(start_i) => {
-                match end_i {
-                    Data::Number(end_i) => {
-                        for i in start_i as usize..end_i as usize {
-                            interpreter.memory.push_scope();
-                            interpreter.memory.insert(self.index_var.to_string(), Data::Number(i as f64));
-
-                            self.body.eval(interpreter);
-                            interpreter.memory.pop_scope();
-                        }
-                    },
-                    _ => ()
-                }
-            },
-            _ => ()
+    fn eval(&self, interpreter: &mut Interpreter) -> Result<Data, RuntimeErr> {
+        let start_i = self.start_i.eval(interpreter)?;
+        let end_i = self.end_i.eval(interpreter)?;
+
+        if let (Data::Number(start_i), Data::Number(end_i)) = (start_i, end_i) {
+            for i in start_i as usize..end_i as usize {
+                interpreter.push_scope();
+                interpreter.memory.insert(self.index_var.to_string(), Data::Number(i as f64));
+
+                let res = self.body.eval(interpreter);
+                interpreter.pop_scope();
+                res?;
+            }
         }
 
-        Data::TempNil
+        Ok(Data::TempNil)
     }
 }
 
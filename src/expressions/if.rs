@@ -1,6 +1,6 @@
 use crate::interpreter::Interpreter;
 use crate::parser::Parser;
-use crate::errors::{ParseErr, ParseErrKind};
+use crate::errors::{ParseErr, RuntimeErr};
 use crate::expressions::{Expr, Evaluable, ExprBlock};
 use crate::data::Data;
 
@@ -31,18 +31,19 @@ impl Evaluable for ExprIf {
         Type::TempNil
     }
 
-    fn eval(&self, interpreter: &mut Interpreter) -> Data {
-        let condition_expr = self.condition_expr.eval(interpreter);
+    fn eval(&self, interpreter: &mut Interpreter) -> Result<Data, RuntimeErr> {
+        let condition_expr = self.condition_expr.eval(interpreter)?;
 
         match condition_expr {
             Data::Bool(val) => {
                 if val {
-                    interpreter.memory.push_scope();
-                    self.body.eval(interpreter);
-                    interpreter.memory.pop_scope();
+                    interpreter.push_scope();
+                    let res = self.body.eval(interpreter);
+                    interpreter.pop_scope();
+                    res?;
                 }
 
-                Data::TempNil
+                Ok(Data::TempNil)
             },
             _ => panic!("temp")
         }
@@ -1,14 +1,17 @@
-use crate::errors::ParseErrKind;
+use std::rc::Rc;
+
+use crate::errors::{ParseErr, ParseErrKind, RuntimeErr};
 use crate::expressions::{Evaluable, Expr};
 use crate::data::Data;
 use crate::interpreter::Interpreter;
 use crate::parser::Parser;
+use crate::tokenizer::{token::Token, token_type::TokenType};
 use crate::types::Type;
 
 #[derive(Debug, Clone)]
 pub struct ExprIndex {
-    index: Box<Expr>,
-    value: Box<Expr>
+    pub index: Box<Expr>,
+    pub value: Box<Expr>
 }
 
 impl ExprIndex {
@@ -22,20 +25,59 @@ impl ExprIndex {
 
 impl Evaluable for ExprIndex {
     fn type_check(&self, parser: &Parser) -> Type {
-        self.value.type_check(parser)
+        match self.value.type_check(parser) {
+            Type::List(item_type) => *item_type,
+            value_type => value_type
+        }
     }
 
-    fn eval(&self, interpreter: &mut Interpreter) -> Data {
-        match self.index.eval(interpreter) {
-            Data::Number(index) => {
+    fn eval(&self, interpreter: &mut Interpreter) -> Result<Data, RuntimeErr> {
+        let index = self.index.eval(interpreter)?;
+        index_data(self.value.eval(interpreter)?, index)
+    }
+}
 
-                match self.value.eval(interpreter) {
-                    Data::String(value) => Data::String(value.chars().collect::<Vec<_>>()[index as usize].to_string()), //very unsafe but idc
-                    _ => unreachable!()
-                }
+/// Gets the character of a string or the item of a list at `index`, which has to be a whole
+/// number within its bounds
+pub fn index_data(value: Data, index: Data) -> Result<Data, RuntimeErr> {
+    let index = match index {
+        Data::Number(index) if index >= 0.0 && index.fract() == 0.0 => index as usize,
+        Data::Number(index) => return Err(RuntimeErr::InvalidIndex(index)),
+        _ => unreachable!()
+    };
 
-            },
-            _ => unreachable!()
-        }
+    match value {
+        Data::String(value) => value.chars()
+            .nth(index)
+            .map(|c| Data::String(Rc::new(c.to_string())))
+            .ok_or_else(|| RuntimeErr::IndexOutOfBounds(index, value.chars().count())),
+        Data::List(items) => items.get(index)
+            .cloned()
+            .ok_or(RuntimeErr::IndexOutOfBounds(index, items.len())),
+        _ => unreachable!()
     }
 }
+
+pub fn parse(parser: &mut Parser, first_token: &Token, expr: Expr) -> Result<Expr, ParseErr> {
+    let index_token = parser.collector.next();
+    let index = Expr::parse_expr(parser, index_token)?;
+
+    let next_token = parser.collector.next();
+    match next_token.token {
+        TokenType::RightBrace => (),
+        _ => return Err(parser.unexpected_token(next_token, "RightBrace"))
+    }
+
+    let value_type = expr.type_check(parser);
+    let index_type = index.type_check(parser);
+
+    match (&value_type, &index_type) {
+        (Type::String | Type::List(_), Type::Number) => (),
+        _ => return Err(
+            ParseErrKind::NotIndexable(value_type.get_name(), index_type.get_name())
+                .from_token(first_token)
+        )
+    }
+
+    Ok(Expr::Index(ExprIndex::new(Box::new(index), Box::new(expr))))
+}
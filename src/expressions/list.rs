@@ -0,0 +1,78 @@
+use std::rc::Rc;
+
+use crate::data::Data;
+use crate::errors::{ParseErr, ParseErrKind, RuntimeErr};
+use crate::expressions::{Evaluable, Expr};
+use crate::helpers::destructive_loop;
+use crate::interpreter::Interpreter;
+use crate::parser::Parser;
+use crate::tokenizer::{token::Token, token_type::TokenType};
+use crate::types::Type;
+
+#[derive(Debug, Clone)]
+pub struct ExprList {
+    pub items: Vec<Expr>
+}
+
+impl ExprList {
+    pub fn new(items: Vec<Expr>) -> ExprList {
+        ExprList {
+            items
+        }
+    }
+}
+
+impl Evaluable for ExprList {
+    fn type_check(&self, parser: &Parser) -> Type {
+        let item_type = self.items.first()
+            .map(|item| item.type_check(parser))
+            .unwrap_or(Type::TempNil);
+
+        Type::List(Box::new(item_type))
+    }
+
+    fn eval(&self, interpreter: &mut Interpreter) -> Result<Data, RuntimeErr> {
+        let items = self.items.iter()
+            .map(|item| item.eval(interpreter))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Data::List(Rc::new(items)))
+    }
+}
+
+/// Parses a list literal like `[1, 2, 3]` after its opening brace, every item has to have the
+/// same type
+pub fn parse(parser: &mut Parser, first_token: &Token) -> Result<Expr, ParseErr> {
+    let mut items: Vec<Expr> = vec![];
+
+    destructive_loop!({
+        let next_token = parser.collector.next();
+        if let TokenType::RightBrace = next_token.token {
+            break
+        }
+
+        let item = Expr::parse_expr(parser, next_token)?;
+
+        if let Some(first_item) = items.first() {
+            let (expected, got) = (first_item.type_check(parser), item.type_check(parser));
+
+            if expected != got {
+                return Err(
+                    ParseErrKind::MixedListItems(expected.get_name(), got.get_name())
+                        .from_token(first_token)
+                );
+            }
+        }
+
+        items.push(item);
+
+        let next_token = parser.collector.next();
+        match &next_token.token {
+            TokenType::Comma => continue,
+            TokenType::RightBrace => break,
+            _ => return Err(parser.unexpected_token(next_token, "Comma or RightBrace"))
+        }
+    });
+
+    Ok(Expr::List(ExprList::new(items)))
+}
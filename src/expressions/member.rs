@@ -0,0 +1,86 @@
+use crate::data::Data;
+use crate::engine::HostClass;
+use crate::errors::{ParseErr, ParseErrKind, RuntimeErr};
+use crate::expressions::{Evaluable, Expr};
+use crate::interpreter::Interpreter;
+use crate::parser::Parser;
+use crate::tokenizer::token::Token;
+use crate::types::Type;
+
+/// Accesses a field of a record value, like `res.status`, or a field or method of a host value
+#[derive(Debug, Clone)]
+pub struct ExprMember {
+    pub value: Box<Expr>,
+    pub field_name: String
+}
+
+impl ExprMember {
+    pub fn new(value: Box<Expr>, field_name: String) -> ExprMember {
+        ExprMember {
+            value,
+            field_name
+        }
+    }
+}
+
+impl ExprMember {
+    /// Type the member can be assigned with, only fields of host values with a setter can
+    pub fn setter_type(&self, parser: &Parser) -> Option<Type> {
+        match self.value.type_check(parser) {
+            Type::Host(id) => HostClass::lookup(&id)?.setter_type(&self.field_name),
+            _ => None
+        }
+    }
+
+    pub fn assign(&self, interpreter: &mut Interpreter, data: Data) -> Result<(), RuntimeErr> {
+        match self.value.eval(interpreter)? {
+            Data::Host(object) => object.set(&self.field_name, data),
+            _ => unreachable!("should have already been caught in parsing")
+        }
+
+        Ok(())
+    }
+}
+
+impl Evaluable for ExprMember {
+    fn type_check(&self, parser: &Parser) -> Type {
+        match self.value.type_check(parser) {
+            Type::Record(fields) => fields.into_iter()
+                .find(|(name, _)| *name == self.field_name)
+                .map(|(_, field_type)| field_type)
+                .expect("should have already been caught in parsing"),
+            Type::Host(id) => HostClass::lookup(&id)
+                .and_then(|class| class.member_type(&self.field_name))
+                .expect("should have already been caught in parsing"),
+            _ => unreachable!("should have already been caught in parsing")
+        }
+    }
+
+    fn eval(&self, interpreter: &mut Interpreter) -> Result<Data, RuntimeErr> {
+        match self.value.eval(interpreter)? {
+            Data::Record(fields) => Ok(
+                fields.iter()
+                    .find(|(name, _)| *name == self.field_name)
+                    .map(|(_, field)| field.clone())
+                    .unwrap()
+            ),
+            Data::Host(object) => Ok(object.get(&self.field_name)),
+            _ => unreachable!()
+        }
+    }
+}
+
+pub fn parse(parser: &mut Parser, first_token: &Token, expr: Expr, field_name: &str) -> Result<Expr, ParseErr> {
+    let value_type = expr.type_check(parser);
+
+    match &value_type {
+        Type::Record(fields) if fields.iter().any(|(name, _)| name == field_name) => (),
+        Type::Host(id) if HostClass::lookup(id).is_some_and(|class| class.member_type(field_name).is_some()) => (),
+        _ => return Err(
+            ParseErrKind::UnknownRecordField(value_type.get_name(), field_name.to_string())
+                .from_token(first_token)
+        )
+    }
+
+    Ok(Expr::Member(ExprMember::new(Box::new(expr), field_name.to_string())))
+}
@@ -2,7 +2,7 @@ use std::fmt::Debug;
 
 use crate::interpreter::Interpreter;
 use crate::parser::Parser;
-use crate::errors::{ParseErr, ParseErrKind, map_err_token};
+use crate::errors::{ParseErr, RuntimeErr};
 use crate::statements::{Executable, ParseableStatement, Statement};
 use crate::tokenizer::{token::Token, token_type::TokenType};
 use crate::data::{Data, ExprLiteral};
@@ -18,6 +18,8 @@ use assign::ExprAssign;
 use block::ExprBlock;
 use r#for::ExprFor;
 use r#if::ExprIf;
+use list::ExprList;
+use member::ExprMember;
 
 pub mod binary;
 pub mod index;
@@ -28,11 +30,13 @@ pub mod assign;
 pub mod block;
 pub mod r#for;
 pub mod r#if;
+pub mod list;
+pub mod member;
 
 pub trait Evaluable: Debug {
     fn type_check(&self, parser: &Parser) -> Type;
 
-    fn eval(&self, interpreter: &mut Interpreter) -> Data;
+    fn eval(&self, interpreter: &mut Interpreter) -> Result<Data, RuntimeErr>;
 
     fn mangle_path(&self) -> Option<String> {
         None
@@ -50,7 +54,9 @@ pub enum Expr {
     Assign(ExprAssign),
     Block(ExprBlock),
     For(ExprFor),
-    If(ExprIf)
+    If(ExprIf),
+    List(ExprList),
+    Member(ExprMember)
 }
 
 impl Expr {
@@ -65,11 +71,15 @@ impl Expr {
             Expr::Assign(assign_expr) => assign_expr.type_check(parser),
             Expr::Block(block_expr) => block_expr.type_check(parser),
             Expr::For(for_expr) => for_expr.type_check(parser),
-            Expr::If(if_expr) => if_expr.type_check(parser)
+            Expr::If(if_expr) => if_expr.type_check(parser),
+            Expr::List(list_expr) => list_expr.type_check(parser),
+            Expr::Member(member_expr) => member_expr.type_check(parser)
         }
     }
 
-    pub fn eval(&self, interpreter: &mut Interpreter) -> Data {
+    pub fn eval(&self, interpreter: &mut Interpreter) -> Result<Data, RuntimeErr> {
+        interpreter.step()?;
+
         match self {
             Expr::Literal(literal_expr) => literal_expr.eval(interpreter),
             Expr::Binary(binary_expr) => binary_expr.eval(interpreter),
@@ -80,7 +90,9 @@ impl Expr {
             Expr::Assign(assign_expr) => assign_expr.eval(interpreter),
             Expr::Block(block_expr) => block_expr.eval(interpreter),
             Expr::For(for_expr) => for_expr.eval(interpreter),
-            Expr::If(if_expr) => if_expr.eval(interpreter)
+            Expr::If(if_expr) => if_expr.eval(interpreter),
+            Expr::List(list_expr) => list_expr.eval(interpreter),
+            Expr::Member(member_expr) => member_expr.eval(interpreter)
         }
     }
 
@@ -102,6 +114,7 @@ impl Expr {
             TokenType::For => r#for::parse(parser, first_token),
             TokenType::If => r#if::parse(parser),
             TokenType::LeftCurly => r#block::parse(parser),
+            TokenType::LeftBrace => list::parse(parser, first_token),
             _ => Err(parser.unexpected_token(first_token, "expression"))
         }
     }
@@ -128,6 +141,7 @@ impl Expr {
 
                     },
                     TokenType::LeftParen => expr = call::parse(parser, next_token, expr)?,
+                    TokenType::LeftBrace => expr = index::parse(parser, next_token, expr)?,
                     _ => {
                         parser.collector.back();
                         break
@@ -141,8 +155,9 @@ impl Expr {
 }
 
 impl Executable for Expr {
-    fn exec(&self, interpreter: &mut Interpreter) {
-        self.eval(interpreter);
+    fn exec(&self, interpreter: &mut Interpreter) -> Result<(), RuntimeErr> {
+        self.eval(interpreter)?;
+        Ok(())
     }
 }
 
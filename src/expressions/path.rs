@@ -1,9 +1,12 @@
+use crate::builtin::BuiltinFn;
 use crate::interpreter::Interpreter;
 use crate::parser::Parser;
-use crate::errors::{ParseErrKind, ParseErr};
+use crate::errors::{ParseErrKind, ParseErr, RuntimeErr};
 use crate::expressions::{Expr, Evaluable};
 use crate::data::Data;
+use crate::memory::{Memory, Slot};
 
+use crate::helpers::destructive_loop;
 use crate::tokenizer::token::Token;
 use crate::tokenizer::token_type::TokenType;
 use crate::types::Type;
@@ -11,14 +14,41 @@ use crate::types::Type;
 #[derive(Debug, Clone)]
 pub struct ExprField {
     pub field_name: String,
-    pub child: Option<Box<Expr>>
+    pub child: Option<Box<Expr>>,
+    /// Filled in by the resolver, `None` if it has to be looked up by name
+    pub slot: Option<Slot>
 }
 
 impl ExprField {
     pub fn new(field_name: String, child: Option<Box<Expr>>) -> Self {
         Self {
             field_name,
-            child
+            child,
+            slot: None
+        }
+    }
+
+    /// Finds the value of the field in `memory`, straight from its slot if it was resolved
+    pub fn lookup<'m, T>(&self, memory: &'m Memory<T>) -> Option<&'m T> {
+        match &self.child {
+            Some(_) => memory.get(&self.mangle_path()?),
+            None => memory.get_at(&self.field_name, self.slot)
+        }
+    }
+
+    /// Like `lookup` but for changing the value in place
+    pub fn lookup_mut<'m, T>(&self, memory: &'m mut Memory<T>) -> Option<&'m mut T> {
+        match &self.child {
+            Some(_) => memory.get_at_mut(&self.mangle_path()?, None),
+            None => memory.get_at_mut(&self.field_name, self.slot)
+        }
+    }
+
+    /// Replaces the value of the field in `memory`, straight in its slot if it was resolved
+    pub fn assign<T>(&self, memory: &mut Memory<T>, value: T) -> Option<T> {
+        match &self.child {
+            Some(_) => memory.assign(self.mangle_path()?, value),
+            None => memory.assign_at(&self.field_name, value, self.slot)
         }
     }
 }
@@ -29,9 +59,8 @@ impl Evaluable for ExprField {
         parser.sim_memory.get(&mangled).unwrap().clone()
     }
 
-    fn eval(&self, interpreter: &mut Interpreter) -> Data {
-        let mangled = self.mangle_path().unwrap();
-        interpreter.memory.get(&mangled).unwrap().clone()
+    fn eval(&self, interpreter: &mut Interpreter) -> Result<Data, RuntimeErr> {
+        Ok(self.lookup(interpreter.memory).unwrap().clone())
     }
 
     fn mangle_path(&self) -> Option<String> {
@@ -44,14 +73,65 @@ impl Evaluable for ExprField {
 }
 
 pub fn parse(parser: &mut Parser, first_token: &Token, expr: Option<Expr>, field_name: &String) -> Result<Expr, ParseErr> {
-    let child = expr.and_then(|expr| Some(Box::new(expr)));
-    let expr_field = ExprField::new(field_name.to_string(), child);
+    if let Some(Type::Record(_) | Type::Host(_)) = expr.as_ref().map(|expr| expr.type_check(parser)) {
+        return super::member::parse(parser, first_token, expr.unwrap(), field_name);
+    }
+
+    if expr.is_some() {
+        let expr_field = ExprField::new(field_name.to_string(), expr.map(Box::new));
+        let mangled = expr_field.mangle_path().unwrap();
 
-    let mangled = expr_field.mangle_path().unwrap();
+        if !parser.sim_memory.has(&mangled) {
+            return Err(ParseErrKind::UnknownField().from_token(first_token));
+        }
 
-    if !parser.sim_memory.has(&mangled) {
-        return Err(ParseErrKind::UnknownField().from_token(first_token));
+        return Ok(Expr::Field(expr_field));
     }
 
-    Ok(Expr::Field(expr_field))
+    let path = parse_path_segments(parser, field_name)?;
+
+    let mangled = parser.resolve_path(&path).ok_or_else(|| {
+        match BuiltinFn::from_name(&path).and_then(|builtin_fn| builtin_fn.capability()) {
+            Some(capability) if !parser.capabilities.allows(capability) => {
+                ParseErrKind::CapabilityDenied(path.to_string(), capability)
+            },
+            _ => ParseErrKind::UnknownField()
+        }
+        .from_token(first_token)
+    })?;
+
+    if !parser.is_visible(&path) {
+        return Err(ParseErrKind::PrivateItem(path).from_token(first_token));
+    }
+
+    Ok(Expr::Field(ExprField::new(mangled, None)))
+}
+
+/// Collects the `::` separated segments following the first segment of a path
+pub fn parse_path_segments(parser: &mut Parser, first_segment: &str) -> Result<String, ParseErr> {
+    let mut path = first_segment.to_string();
+
+    destructive_loop!({
+        let next_token = parser.collector.next();
+        match next_token.token {
+            TokenType::DoubleColon => {
+
+                let next_token = parser.collector.next();
+                match &next_token.token {
+                    TokenType::Identifier(segment) => {
+                        path.push_str("::");
+                        path.push_str(segment);
+                    },
+                    _ => return Err(parser.unexpected_token(next_token, "path segment"))
+                }
+
+            },
+            _ => {
+                parser.collector.back();
+                break
+            }
+        }
+    });
+
+    Ok(path)
 }
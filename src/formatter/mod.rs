@@ -0,0 +1,137 @@
+use crate::errors::ParseErr;
+use crate::tokenizer::Lexer;
+use crate::tokenizer::token_type::TokenType;
+
+const INDENT: &str = "    ";
+
+/// How a single line of the source is written back out
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Line {
+    /// Re-indented to the given depth, trimmed on both ends
+    Indented(usize),
+    /// Re-indented but only trimmed at the start, as a multi-line string or comment begins on it
+    OpensMultiline(usize),
+    /// Inside a multi-line string or block comment, left exactly as it is
+    Verbatim,
+    /// Contains nothing but whitespace
+    Blank
+}
+
+/// Formats a source buffer: re-indents lines by bracket depth, trims trailing whitespace,
+/// collapses runs of blank lines and ends the file with a single newline
+pub fn format_source(filename: &str, buf: &str) -> Result<String, ParseErr> {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(buf.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let line_of = |byte_i: usize| line_starts.partition_point(|start| *start <= byte_i) - 1;
+
+    let mut lines = vec![Line::Blank; line_starts.len()];
+    let mut depth: usize = 0;
+
+    for token in Lexer::new(filename, buf).keep_comments() {
+        let token = token?;
+        let (start, end) = token.span;
+
+        if let TokenType::EOF = token.token {
+            break;
+        }
+
+        let closes = matches!(token.token, TokenType::RightParen | TokenType::RightBrace | TokenType::RightCurly);
+        if closes {
+            depth = depth.saturating_sub(1);
+        }
+
+        let first_line = line_of(start);
+        let last_line = line_of(end - 1);
+
+        if lines[first_line] == Line::Blank {
+            lines[first_line] = Line::Indented(depth);
+        }
+
+        if last_line > first_line {
+            if let Line::Indented(line_depth) = lines[first_line] {
+                lines[first_line] = Line::OpensMultiline(line_depth);
+            }
+
+            for line in lines.iter_mut().take(last_line + 1).skip(first_line + 1) {
+                *line = Line::Verbatim;
+            }
+        }
+
+        if matches!(token.token, TokenType::LeftParen | TokenType::LeftBrace | TokenType::LeftCurly) {
+            depth += 1;
+        }
+    }
+
+    let mut formatted = String::new();
+    let mut pending_blank = false;
+
+    for (line_i, line) in lines.iter().enumerate() {
+        let end = line_starts.get(line_i + 1).map_or(buf.len(), |next_start| next_start - 1);
+        let text = &buf[line_starts[line_i]..end];
+
+        match line {
+            Line::Blank => {
+                pending_blank = !formatted.is_empty();
+                continue;
+            },
+            _ if pending_blank => {
+                formatted.push('\n');
+                pending_blank = false;
+            },
+            _ => ()
+        }
+
+        match line {
+            Line::Indented(depth) => {
+                formatted.push_str(&INDENT.repeat(*depth));
+                formatted.push_str(text.trim());
+            },
+            Line::OpensMultiline(depth) => {
+                formatted.push_str(&INDENT.repeat(*depth));
+                formatted.push_str(text.trim_start());
+            },
+            Line::Verbatim => formatted.push_str(text),
+            Line::Blank => unreachable!()
+        }
+
+        formatted.push('\n');
+    }
+
+    Ok(formatted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_source;
+
+    #[test]
+    fn reindents_blocks() {
+        let source = "fn f(x: Number) {\nif x == 1 {\n  print(x);\n    }\n}\n";
+        let expected = "fn f(x: Number) {\n    if x == 1 {\n        print(x);\n    }\n}\n";
+
+        assert_eq!(format_source("test.ox", source).unwrap(), expected);
+    }
+
+    #[test]
+    fn trims_and_collapses_blank_lines() {
+        let source = "\n\nlet a = 1;   \n\n\n\nlet b = 2;\n\n\n";
+
+        assert_eq!(format_source("test.ox", source).unwrap(), "let a = 1;\n\nlet b = 2;\n");
+    }
+
+    #[test]
+    fn keeps_multiline_strings_and_comments() {
+        let source = "{\n/* a\n   b */\nlet s = \"x  \n  y\";\n}";
+        let expected = "{\n    /* a\n   b */\n    let s = \"x  \n  y\";\n}\n";
+
+        assert_eq!(format_source("test.ox", source).unwrap(), expected);
+    }
+
+    #[test]
+    fn is_idempotent() {
+        let source = "fn f() {\n    // comment\n    print(\"hi\");\n}\n";
+
+        assert_eq!(format_source("test.ox", source).unwrap(), source);
+    }
+}
@@ -1,24 +1,203 @@
+use std::io::{self, BufWriter, Write};
+
 use crate::memory::Memory;
+use crate::sandbox::Capabilities;
 use crate::data::Data;
+use crate::errors::RuntimeErr;
+use crate::expressions::Evaluable;
 use crate::statements::Statement;
+use crate::statements::r#fn::FunctionDeclaration;
+
+/// How much a program may do before it's stopped with a runtime error, `None` means unlimited
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    /// Expressions evaluated in total, or instructions run when using the VM
+    pub max_steps: Option<u64>,
+    /// Nested calls of user defined functions
+    pub max_depth: Option<usize>,
+    /// Bytes held by the strings and collections in memory, see [`Data::heap_size`]
+    pub max_memory: Option<usize>
+}
+
+impl Limits {
+    /// Deep enough for any reasonable recursion while staying well clear of overflowing the stack
+    pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+    pub fn unlimited() -> Self {
+        Self {
+            max_steps: None,
+            max_depth: None,
+            max_memory: None
+        }
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_depth: Some(Self::DEFAULT_MAX_DEPTH),
+            ..Self::unlimited()
+        }
+    }
+}
 
 pub struct Interpreter<'a, 'm> {
     pub statements: &'a Vec<Statement>,
-    pub memory: &'m mut Memory<Data>
+    pub memory: &'m mut Memory<Data>,
+    /// Arguments passed to the script on the command line, returned by the `args` builtin
+    pub script_args: Vec<String>,
+    /// What the program is allowed to do, checked again whenever a builtin is called
+    pub capabilities: Capabilities,
+    pub limits: Limits,
+    steps: u64,
+    depth: usize,
+    /// Bytes held by the values in memory, counted when the memory limit is first checked and
+    /// kept up to date from then on
+    memory_used: Option<usize>,
+    /// Where the print builtins write to, buffered and flushed when the interpreter is dropped
+    pub stdout: Box<dyn Write>
 }
 
 impl<'a, 'm> Interpreter<'a, 'm> {
-    pub fn new(statements: &'a Vec<Statement>, memory: &'m mut Memory<Data>) -> Self {
+    pub fn new(statements: &'a Vec<Statement>, memory: &'m mut Memory<Data>, capabilities: Capabilities) -> Self {
         Self {
             statements,
             memory,
+            script_args: vec![],
+            capabilities,
+            limits: Limits::default(),
+            steps: 0,
+            depth: 0,
+            memory_used: None,
+            stdout: Box::new(BufWriter::new(io::stdout()))
         }
     }
 
-    pub fn run_program(&mut self) {
-        for statement in self.statements.into_iter() {
-            statement.exec(self);
+    /// Writes out everything printed so far, needed before reading input or writing to stderr
+    /// so output shows up in the order the program produced it
+    pub fn flush_stdout(&mut self) {
+        let _ = self.stdout.flush();
+    }
+
+    /// Counts an evaluated expression, or an instruction of the VM, against the step limit
+    pub fn step(&mut self) -> Result<(), RuntimeErr> {
+        self.steps += 1;
+
+        match self.limits.max_steps {
+            Some(max_steps) if self.steps > max_steps => Err(RuntimeErr::StepLimitExceeded(max_steps)),
+            _ => Ok(())
+        }
+    }
+
+    fn memory_used(&mut self) -> usize {
+        *self.memory_used.get_or_insert_with(|| {
+            self.memory.scopes.iter()
+                .flat_map(|scope| scope.values())
+                .map(Data::heap_size)
+                .sum()
+        })
+    }
+
+    /// Checks that the program stays under the memory limit with `data` added to it, called
+    /// whenever a builtin produces a value
+    pub fn check_memory(&mut self, data: &Data) -> Result<(), RuntimeErr> {
+        let Some(max_memory) = self.limits.max_memory else {
+            return Ok(());
+        };
+
+        match self.memory_used() + data.heap_size() > max_memory {
+            true => Err(RuntimeErr::MemoryLimitExceeded(max_memory)),
+            false => Ok(())
+        }
+    }
+
+    /// Counts `data` against the memory limit as it's stored in place of a value whose size
+    /// `replaced_size` gives, failing instead if that would go over the limit. Called whenever
+    /// a value is stored, `replaced_size` is only called when there is a limit.
+    pub fn store_memory<F>(&mut self, data: &Data, replaced_size: F) -> Result<(), RuntimeErr>
+    where
+        F: FnOnce(&Memory<Data>) -> usize
+    {
+        let Some(max_memory) = self.limits.max_memory else {
+            return Ok(());
+        };
+
+        let used = self.memory_used().saturating_sub(replaced_size(self.memory)) + data.heap_size();
+        if used > max_memory {
+            return Err(RuntimeErr::MemoryLimitExceeded(max_memory));
         }
+
+        self.memory_used = Some(used);
+        Ok(())
+    }
+
+    /// Stops counting values that were dropped from memory against the memory limit
+    pub fn free_memory<'d>(&mut self, values: impl Iterator<Item = &'d Data>) {
+        if let Some(used) = &mut self.memory_used {
+            *used = used.saturating_sub(values.map(Data::heap_size).sum());
+        }
+    }
+
+    /// Forgets how much memory is used, for when values were dropped from memory without
+    /// going through [`Interpreter::free_memory`]
+    pub fn recount_memory(&mut self) {
+        self.memory_used = None;
+    }
+
+    pub fn push_scope(&mut self) {
+        self.memory.push_scope();
+    }
+
+    /// Pops the innermost scope, no longer counting its values against the memory limit
+    pub fn pop_scope(&mut self) {
+        let scope = self.memory.pop_scope();
+        self.free_memory(scope.values());
+    }
+
+    pub fn run_program(&mut self) -> Result<(), RuntimeErr> {
+        for statement in self.statements.iter() {
+            statement.exec(self)?;
+        }
+
+        Ok(())
+    }
+
+    /// Counts a call of a user defined function against the depth limit until the matching
+    /// `exit_call`, shared by both backends so calls between them are counted too
+    pub fn enter_call(&mut self) -> Result<(), RuntimeErr> {
+        if let Some(max_depth) = self.limits.max_depth {
+            if self.depth >= max_depth {
+                return Err(RuntimeErr::CallDepthExceeded(max_depth));
+            }
+        }
+
+        self.depth += 1;
+        Ok(())
+    }
+
+    pub fn exit_call(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Calls a user defined function with already evaluated arguments
+    pub fn call_fn(&mut self, fn_decl: &FunctionDeclaration, args: Vec<Data>) -> Result<Data, RuntimeErr> {
+        self.enter_call()?;
+        self.push_scope();
+
+        for ((arg_name, _), arg_data) in fn_decl.args.iter().zip(args) {
+            if let Err(err) = self.store_memory(&arg_data, |_| 0) {
+                self.pop_scope();
+                self.exit_call();
+                return Err(err);
+            }
+            self.memory.insert(arg_name.to_string(), arg_data);
+        }
+
+        let res = fn_decl.body.eval(self);
+        self.pop_scope();
+        self.exit_call();
+
+        res
     }
 }
 
@@ -1,4 +1,16 @@
-use crate::helpers::destructive_loop;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub use engine::{Engine, Value};
+pub use errors::Error;
+
+use interpreter::Limits;
+use manifest::Manifest;
+use optimizer::OptLevel;
+use dump::AstFormat;
+use sandbox::Capabilities;
 
 pub mod tokenizer;
 pub mod parser;
@@ -11,40 +23,317 @@ pub mod operations;
 pub mod types;
 pub mod builtin;
 pub mod errors;
+pub mod manifest;
+pub mod formatter;
+pub mod sandbox;
+pub mod engine;
+pub mod vm;
+pub mod resolver;
+pub mod optimizer;
+pub mod visitor;
+pub mod dump;
 pub mod helpers;
 
+pub const USAGE: &str = "\
+Usage: oxid [options] [command] [file | -e <code> | -] [--] [args...]
+
+Commands:
+    run      Run a script, or the project in the current directory (default)
+    check    Parse and type check a script without running it
+    fmt      Format a script in place, or to stdout when reading from stdin
+    repl     Start an interactive session
+    test     Run every `test_*` function in a script
+
+Options:
+    -e <code>          Run the given code instead of a file
+    -                  Read the program from stdin
+    --std <file>       Use the given file as the standard library
+    --no-std           Don't load the standard library
+    --lib-dir <dir>    Add a directory to the module search path
+    --sandbox          Deny the script access to files and importing modules from disk
+    --allow-process    Allow the script to read and change its environment and run programs
+    --max-steps <n>    Stop the script after evaluating n expressions, or n instructions on the vm
+    --max-depth <n>    Limit nested function calls to n, 256 by default. The stack is sized
+                       from n, so large limits reserve more memory instead of overflowing
+    --max-memory <n>   Limit the strings and collections the script holds to n bytes
+    --backend <name>   Run scripts with `tree`, walking the syntax tree (default), or `vm`,
+                       compiling them to bytecode first. The repl always uses `tree`
+    --opt-level <n>    Optimize scripts before running them: 0 runs them as written (default),
+                       1 folds constant operations and drops `if false` bodies, 2 also
+                       inlines immutable variables set to a constant
+    --dump-tokens      Print the tokens of the script instead of running it
+    --dump-ast[=json]  Print the syntax tree of the script, after optimizing, instead of running
+                       it. Indented text by default or JSON
+    -h, --help         Print this help
+    -V, --version      Print the version
+
+Arguments after the file or `--` are passed to the script, see `args()`.
+
+Exit status:
+    0      Success
+    1      Runtime error, failed tests or errors found by `check`
+    2      Invalid command line arguments or project manifest
+    3      Parse or type error
+    4      Failed to read or write a file
+    101    Internal error in oxid itself
+    n      The script called `exit(n)`, n is taken modulo 256. Scripts can exit with any of
+           the codes above, use codes over 4 to tell your own failures apart from oxid's";
+
+/// Exit codes of the `oxid` binary, documented in [`USAGE`]
+pub mod exit_code {
+    pub const SUCCESS: u8 = 0;
+    pub const FAILURE: u8 = 1;
+    pub const RUNTIME_ERR: u8 = 1;
+    pub const USAGE_ERR: u8 = 2;
+    pub const PARSE_ERR: u8 = 3;
+    pub const IO_ERR: u8 = 4;
+    /// What Rust exits with after a panic
+    pub const INTERNAL_ERR: u8 = 101;
+
+    /// Exit code for `exit(code)`, wrapped into a byte like shells do
+    pub fn from_exit(code: i32) -> u8 {
+        code.rem_euclid(256) as u8
+    }
+}
+
+/// How programs are run
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Backend {
+    /// Evaluating the syntax tree directly, see [`interpreter::Interpreter`]
+    #[default]
+    Tree,
+    /// Compiling to bytecode and running it on a stack machine, see [`vm`]
+    Vm
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Run,
+    Check,
+    Fmt,
+    Repl,
+    Test,
+    Help,
+    Version
+}
+
+/// Where the program comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    File(String),
+    Stdin,
+    Inline(String)
+}
+
+impl Source {
+    /// Name used for the source in error messages
+    pub fn filename(&self) -> String {
+        match self {
+            Source::File(filename) => filename.to_string(),
+            Source::Stdin => String::from("<stdin>"),
+            Source::Inline(_) => String::from("<inline>")
+        }
+    }
+
+    pub fn read(&self) -> Result<String, String> {
+        match self {
+            Source::File(filename) => fs::read_to_string(filename)
+                .map_err(|err| format!("failed to read file `{}`: {}", filename, err)),
+            Source::Stdin => io::read_to_string(io::stdin())
+                .map_err(|err| format!("failed to read stdin: {}", err)),
+            Source::Inline(code) => Ok(code.to_string())
+        }
+    }
+}
+
 pub struct Config {
-    pub source_file: String,
-    pub std_file: Option<String>
+    pub command: Command,
+    /// Only `None` for commands that don't need a program, like `repl` and `help`
+    pub source: Option<Source>,
+    /// Arguments passed on to the script, returned by the `args` builtin
+    pub script_args: Vec<String>,
+    /// Replaces the standard library embedded in the binary
+    pub std_file: Option<String>,
+    pub no_std: bool,
+    pub lib_dirs: Vec<String>,
+    /// Starts from no capabilities instead of the default ones
+    pub sandbox: bool,
+    /// Enables the builtins for the process environment and running programs
+    pub allow_process: bool,
+    pub limits: Limits,
+    pub backend: Backend,
+    pub opt_level: OptLevel,
+    pub dump_tokens: bool,
+    pub dump_ast: Option<AstFormat>,
+    pub manifest: Option<Manifest>
 }
 
 impl Config {
-    pub fn new(args: &mut impl Iterator<Item = String>) -> Config {
+    pub fn new(args: &mut impl Iterator<Item = String>) -> Result<Config, String> {
         args.next();
 
+        let mut command = None;
+        let mut source = None;
+        let mut script_args = vec![];
         let mut std_file = None;
+        let mut no_std = false;
+        let mut lib_dirs = vec![];
+        let mut sandbox = false;
+        let mut allow_process = false;
+        let mut limits = Limits::default();
+        let mut backend = Backend::default();
+        let mut opt_level = OptLevel::default();
+        let mut dump_tokens = false;
+        let mut dump_ast = None;
 
-        loop {
-            match args.next() {
-                Some(arg) => {
+        while let Some(arg) = args.next() {
+            match &arg[..] {
+                "-h" | "--help" => command = Some(Command::Help),
+                "-V" | "--version" => command = Some(Command::Version),
+                "--std" => {
+                    std_file = Some(args.next().ok_or("`--std` expects a file")?)
+                },
+                "--no-std" => no_std = true,
+                "--sandbox" => sandbox = true,
+                "--allow-process" => allow_process = true,
+                "--max-steps" => limits.max_steps = Some(parse_limit(&arg, args.next())?),
+                "--max-depth" => limits.max_depth = Some(parse_limit(&arg, args.next())?),
+                "--max-memory" => limits.max_memory = Some(parse_limit(&arg, args.next())?),
+                _ if arg == "--backend" || arg.starts_with("--backend=") => {
+                    let name = match arg.strip_prefix("--backend=") {
+                        Some(name) => name.to_string(),
+                        None => args.next().ok_or("`--backend` expects `tree` or `vm`")?
+                    };
+
+                    backend = match &name[..] {
+                        "tree" => Backend::Tree,
+                        "vm" => Backend::Vm,
+                        _ => return Err(format!("unknown backend `{}`, expected `tree` or `vm`", name))
+                    };
+                },
+                _ if arg == "--opt-level" || arg.starts_with("--opt-level=") => {
+                    let level = match arg.strip_prefix("--opt-level=") {
+                        Some(level) => level.to_string(),
+                        None => args.next().ok_or("`--opt-level` expects 0, 1 or 2")?
+                    };
 
-                    match &arg[..] {
-                        "--std" => {
-                            std_file = Some(args.next().expect("std file not specified"))
-                        },
+                    opt_level = OptLevel::from_number(&level)
+                        .ok_or_else(|| format!("unknown optimization level `{}`, expected 0, 1 or 2", level))?;
+                },
+                "--dump-tokens" => dump_tokens = true,
+                "--dump-ast" | "--dump-ast=text" => dump_ast = Some(AstFormat::Text),
+                "--dump-ast=json" => dump_ast = Some(AstFormat::Json),
+                "--lib-dir" => {
+                    lib_dirs.push(args.next().ok_or("`--lib-dir` expects a directory")?)
+                },
+                "-e" => {
+                    source = Some(Source::Inline(args.next().ok_or("`-e` expects code to run")?));
+                    break;
+                },
+                "-" => {
+                    source = Some(Source::Stdin);
+                    break;
+                },
+                "--" => break,
+                _ if arg.starts_with('-') => return Err(format!("unknown option `{}`", arg)),
+                _ if command.is_none() => {
+                    command = Some(match &arg[..] {
+                        "run" => Command::Run,
+                        "check" => Command::Check,
+                        "fmt" => Command::Fmt,
+                        "repl" => Command::Repl,
+                        "test" => Command::Test,
                         _ => {
-                            return Config {
-                                source_file: arg,
-                                std_file
-                            }
+                            source = Some(Source::File(arg));
+                            break;
                         }
-                    }
-
+                    })
                 },
-                None => panic!("source file not specified")
+                _ => {
+                    source = Some(Source::File(arg));
+                    break;
+                }
             }
         }
+
+        let command = command.unwrap_or(Command::Run);
+
+        // everything after the source is passed to the script, an optional `--` separates them
+        let mut rest = args.peekable();
+        if source.is_some() && rest.peek().is_some_and(|arg| arg == "--") {
+            rest.next();
+        }
+        script_args.extend(rest);
+
+        if matches!(command, Command::Help | Command::Version | Command::Repl) {
+            return Ok(Config { command, source, script_args, std_file, no_std, lib_dirs, sandbox, allow_process, limits, backend, opt_level, dump_tokens, dump_ast, manifest: None });
+        }
+
+        // without a source the entry point of the project in the current directory is used
+        let manifest_path = match &source {
+            Some(Source::File(source_file)) => {
+                let source_dir = Path::new(source_file).parent().unwrap_or(Path::new(""));
+                fs::canonicalize(source_dir).ok().and_then(|source_dir| Manifest::find(&source_dir))
+            },
+            Some(_) => None,
+            None => {
+                let cwd = env::current_dir().map_err(|err| format!("failed to get the current directory: {}", err))?;
+                Some(Manifest::find(&cwd).ok_or("no source file given and no Oxid.toml found in the current directory or its parents")?)
+            }
+        };
+
+        let manifest = manifest_path.map(|manifest_path| Manifest::load(&manifest_path)).transpose()?;
+
+        let source = source.or_else(|| {
+            let entry_path = manifest.as_ref()?.entry_path();
+            Some(Source::File(entry_path.to_string_lossy().to_string()))
+        });
+
+        Ok(Config {
+            command,
+            source,
+            script_args,
+            std_file,
+            no_std,
+            lib_dirs,
+            sandbox,
+            allow_process,
+            limits,
+            backend,
+            opt_level,
+            dump_tokens,
+            dump_ast,
+            manifest
+        })
     }
+
+    pub fn capabilities(&self) -> Capabilities {
+        let mut capabilities = if self.sandbox { Capabilities::none() } else { Capabilities::default() };
+
+        if self.allow_process {
+            capabilities.process = true;
+            capabilities.env = true;
+        }
+
+        capabilities
+    }
+
+    /// Directories searched for modules, `--lib-dir`s first and then those in `OXID_PATH`
+    pub fn search_paths(&self) -> Vec<PathBuf> {
+        let mut search_paths: Vec<_> = self.lib_dirs.iter().map(PathBuf::from).collect();
+
+        if let Some(oxid_path) = env::var_os("OXID_PATH") {
+            search_paths.extend(env::split_paths(&oxid_path));
+        }
+
+        search_paths
+    }
+}
+
+fn parse_limit<T: std::str::FromStr>(option: &str, value: Option<String>) -> Result<T, String> {
+    value.as_deref()
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| format!("`{}` expects a whole number", option))
 }
 
 #[cfg(test)]
@@ -1,43 +1,446 @@
+use std::any::Any;
+use std::io::{self, Write};
+use std::panic::{self, AssertUnwindSafe};
 use std::process::ExitCode;
 use std::fs;
 use std::env;
-use oxid::Config;
+use std::thread;
+use oxid::{exit_code, Backend, Command, Config, Source, USAGE};
 use oxid::builtin::BuiltinFn;
 use oxid::data::Data;
-use oxid::interpreter::Interpreter;
-use oxid::memory::Memory;
+use oxid::dump::{dump_ast, dump_tokens};
+use oxid::errors::{ParseErr, ParseErrKind, RuntimeErr};
+use oxid::formatter::format_source;
+use oxid::interpreter::{Interpreter, Limits};
+use oxid::memory::{Memory, Scope};
+use oxid::optimizer::{optimize, OptLevel};
 use oxid::parser::{Parser, TokenCollector};
-use oxid::tokenizer::tokenize;
+use oxid::sandbox::Capabilities;
+use oxid::statements::Statement;
+use oxid::statements::r#fn::FunctionDeclaration;
+use oxid::statements::module::{ModuleImport, ModuleRegistry, STD_SOURCE};
+use oxid::tokenizer::{tokenize, Lexer};
+use oxid::tokenizer::token_type::TokenType;
 use oxid::types::Type;
+use oxid::vm;
 
-fn execute_file(filename: &str, sim_memory: &mut Memory<Type>, memory: &mut Memory<Data>) {
-    let buf = fs::read_to_string(filename)
-        .expect(&format!("failed to read file `{}`", filename));
-    let buf = buf.trim();
+/// Why a command stopped early, each kind has its own exit code
+enum Failure {
+    /// A project manifest or its dependencies are invalid
+    Usage(String),
+    Io(String),
+    Parse(Vec<ParseErr>),
+    Runtime(RuntimeErr)
+}
+
+impl Failure {
+    fn report(&self) -> ExitCode {
+        match self {
+            Failure::Usage(err) => {
+                eprintln!("error: {}", err);
+                exit_code::USAGE_ERR.into()
+            },
+            Failure::Io(err) => {
+                eprintln!("error: {}", err);
+                exit_code::IO_ERR.into()
+            },
+            Failure::Parse(errs) => {
+                for err in errs.iter() {
+                    eprintln!("error: {}", err);
+                }
+                exit_code::PARSE_ERR.into()
+            },
+            Failure::Runtime(RuntimeErr::Exit(code)) => exit_code::from_exit(*code).into(),
+            Failure::Runtime(err) => {
+                eprintln!("error: {}", err);
+                exit_code::RUNTIME_ERR.into()
+            }
+        }
+    }
+}
+
+/// Everything that outlives a single parsed program, shared between the lines of the repl
+struct Session {
+    sim_memory: Memory<Type>,
+    memory: Memory<Data>,
+    modules: ModuleRegistry,
+    aliases: Scope<String>,
+    script_args: Vec<String>,
+    capabilities: Capabilities,
+    limits: Limits,
+    backend: Backend,
+    opt_level: OptLevel
+}
+
+impl Session {
+    fn new(config: &Config) -> Result<Self, Failure> {
+        let capabilities = config.capabilities();
+
+        let mut sim_memory = Memory::new();
+        BuiltinFn::populate_sim_memory(&mut sim_memory, &capabilities);
+
+        let mut memory = Memory::new();
+        BuiltinFn::populate_memory(&mut memory, &capabilities);
+
+        let mut modules = ModuleRegistry::default();
+        modules.search_paths = config.search_paths();
+
+        if let Some(manifest) = &config.manifest {
+            modules.dependencies = manifest.resolve_dependencies().map_err(Failure::Usage)?;
+        }
+
+        let mut session = Self {
+            sim_memory,
+            memory,
+            modules,
+            aliases: Scope::default(),
+            script_args: config.script_args.clone(),
+            capabilities,
+            limits: config.limits,
+            backend: config.backend,
+            opt_level: config.opt_level
+        };
+
+        if !config.no_std {
+            session.load_std(config)?;
+        }
+
+        Ok(session)
+    }
+
+    fn load_std(&mut self, config: &Config) -> Result<(), Failure> {
+        let (filename, buf) = match &config.std_file {
+            Some(std_file) => {
+                let buf = fs::read_to_string(std_file)
+                    .map_err(|err| Failure::Io(format!("failed to read file `{}`: {}", std_file, err)))?;
+                (std_file.to_string(), buf)
+            },
+            None => (String::from("<std>"), STD_SOURCE.to_string())
+        };
+
+        let std_import = ModuleImport::parse_std(&filename, &buf, &mut self.sim_memory, &mut self.modules)
+            .map_err(|err| Failure::Parse(vec![err]))?;
+
+        // checking never runs anything, not even a replacement std
+        if config.command != Command::Check {
+            let mut statements = vec![Statement::ModuleImport(std_import)];
+            optimize(&mut statements, self.opt_level);
+            self.run(&statements)?;
+        }
+
+        Ok(())
+    }
+
+    /// Parses, type checks and optimizes a program, collecting every error
+    fn try_parse(&mut self, filename: &str, buf: &str) -> Result<Vec<Statement>, Vec<ParseErr>> {
+        let tokens = tokenize(filename, buf).map_err(|err| vec![err])?;
+        let collector = TokenCollector::new(&tokens);
+
+        let mut parser = Parser::new(collector, &mut self.sim_memory);
+        parser.modules = std::mem::take(&mut self.modules);
+        parser.aliases = std::mem::take(&mut self.aliases);
+        parser.capabilities = self.capabilities;
+        let mut res = parser.parse_program();
 
-    let tokens = tokenize(&String::from(filename), buf);
-    let collector = TokenCollector::new(&tokens);
+        self.modules = parser.modules;
+        self.aliases = parser.aliases;
 
-    let mut parser = Parser::new(collector, sim_memory);
-    let statements = parser.generate_program();
-    let mut interpreter = Interpreter::new(&statements, memory);
-    interpreter.run_program();
+        if let Ok(statements) = &mut res {
+            optimize(statements, self.opt_level);
+        }
+
+        res
+    }
+
+    fn interpreter<'a>(&'a mut self, statements: &'a Vec<Statement>) -> Interpreter<'a, 'a> {
+        let mut interpreter = Interpreter::new(statements, &mut self.memory, self.capabilities);
+        interpreter.script_args = self.script_args.clone();
+        interpreter.limits = self.limits;
+        interpreter
+    }
+
+    fn run(&mut self, statements: &Vec<Statement>) -> Result<(), Failure> {
+        let res = match self.backend {
+            Backend::Tree => self.interpreter(statements).run_program(),
+            Backend::Vm => vm::run(&mut self.interpreter(statements), &vm::compile(statements))
+        };
+
+        res.map_err(Failure::Runtime)
+    }
+
+    /// Calls a function declared by a program that already ran
+    fn call(&mut self, statements: &Vec<Statement>, fn_decl: &FunctionDeclaration) -> Result<Data, RuntimeErr> {
+        match self.memory.get(&fn_decl.name).cloned() {
+            Some(Data::VmFn(function)) => vm::call(&mut self.interpreter(statements), &function, vec![]),
+            _ => self.interpreter(statements).call_fn(fn_decl, vec![])
+        }
+    }
+}
+
+fn read_source(config: &Config) -> Result<(String, String), Failure> {
+    let source = config.source.as_ref().expect("source should have been resolved by the config");
+    let buf = source.read().map_err(Failure::Io)?;
+
+    Ok((source.filename(), buf))
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload.downcast_ref::<&str>().map(|msg| msg.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| String::from("unknown error"))
 }
 
+/// Prints the tokens and/or syntax tree of the program instead of running it
+fn dump(config: &Config) -> Result<ExitCode, Failure> {
+    let (filename, buf) = read_source(config)?;
+
+    if config.dump_tokens {
+        let tokens = tokenize(&filename, buf.trim()).map_err(|err| Failure::Parse(vec![err]))?;
+        print!("{}", dump_tokens(&tokens));
+    }
+
+    if let Some(format) = config.dump_ast {
+        let mut session = Session::new(config)?;
+        let statements = session.try_parse(&filename, buf.trim()).map_err(Failure::Parse)?;
+        print!("{}", dump_ast(&statements, format));
+    }
+
+    Ok(exit_code::SUCCESS.into())
+}
+
+fn run(config: &Config) -> Result<ExitCode, Failure> {
+    let mut session = Session::new(config)?;
+    let (filename, buf) = read_source(config)?;
+    let statements = session.try_parse(&filename, buf.trim()).map_err(Failure::Parse)?;
+    session.run(&statements)?;
+
+    Ok(exit_code::SUCCESS.into())
+}
+
+/// Parses and type checks the program and the modules it imports without running anything,
+/// printing every error found
+fn check(config: &Config) -> Result<ExitCode, Failure> {
+    let mut session = Session::new(config)?;
+    let (filename, buf) = read_source(config)?;
+
+    match session.try_parse(&filename, buf.trim()) {
+        Ok(_) => Ok(exit_code::SUCCESS.into()),
+        Err(errs) => {
+            for err in errs.iter() {
+                eprintln!("error: {}", err);
+            }
+
+            let plural = if errs.len() == 1 { "" } else { "s" };
+            eprintln!("\n{}: {} error{} found", filename, errs.len(), plural);
+            Ok(exit_code::FAILURE.into())
+        }
+    }
+}
+
+fn fmt(config: &Config) -> Result<ExitCode, Failure> {
+    let (filename, buf) = read_source(config)?;
+    let formatted = format_source(&filename, &buf).map_err(|err| Failure::Parse(vec![err]))?;
+
+    match config.source {
+        Some(Source::File(_)) if formatted != buf => {
+            fs::write(&filename, formatted)
+                .map_err(|err| Failure::Io(format!("failed to write file `{}`: {}", filename, err)))?;
+        },
+        Some(Source::File(_)) => (),
+        _ => print!("{}", formatted)
+    }
+
+    Ok(exit_code::SUCCESS.into())
+}
+
+/// Runs every top level `test_*` function without arguments, each failing independently
+fn test(config: &Config) -> Result<ExitCode, Failure> {
+    let mut session = Session::new(config)?;
+    let (filename, buf) = read_source(config)?;
+    let statements = session.try_parse(&filename, buf.trim()).map_err(Failure::Parse)?;
+    session.run(&statements)?;
+
+    let tests: Vec<_> = statements.iter()
+        .filter_map(|statement| match statement {
+            Statement::FunctionDeclaration(fn_decl) if fn_decl.name.starts_with("test_") && fn_decl.args.is_empty() => Some(fn_decl),
+            _ => None
+        })
+        .collect();
+
+    println!("running {} tests", tests.len());
+
+    let mut failed = 0;
+    for fn_decl in tests.iter() {
+        match session.call(&statements, fn_decl) {
+            // `exit` only ends the test calling it, a zero code counts as a pass
+            Ok(_) | Err(RuntimeErr::Exit(0)) => println!("test {} ... ok", fn_decl.name),
+            Err(err) => {
+                failed += 1;
+                println!("test {} ... FAILED: {}", fn_decl.name, err);
+            }
+        }
+    }
+
+    let status = if failed == 0 { "ok" } else { "FAILED" };
+    println!("\ntest result: {}. {} passed; {} failed", status, tests.len() - failed, failed);
+
+    Ok(if failed == 0 { exit_code::SUCCESS.into() } else { exit_code::FAILURE.into() })
+}
+
+/// How many more brackets have been opened than closed, the repl keeps reading while positive.
+/// Brackets inside strings and comments don't count, an unterminated string or block comment
+/// keeps the input open
+fn open_brackets(input: &str) -> isize {
+    let mut depth = 0;
+
+    for token in Lexer::new("<repl>", input).keep_comments() {
+        match token {
+            Ok(token) => match token.token {
+                TokenType::LeftParen | TokenType::LeftBrace | TokenType::LeftCurly => depth += 1,
+                TokenType::RightParen | TokenType::RightBrace | TokenType::RightCurly => depth -= 1,
+                _ => ()
+            },
+            Err(err) => match err.err_kind {
+                ParseErrKind::UnmatchedDelimiter('"') | ParseErrKind::UnterminatedBlockComment() => return depth.max(1),
+                // anything else is left for the parser to report
+                _ => return depth
+            }
+        }
+    }
+
+    depth
+}
+
+/// Parses and runs a single input of the repl, printing the value of expression statements
+fn eval_repl_input(session: &mut Session, buf: &str) -> Result<(), Failure> {
+    let statements = session.try_parse("<repl>", buf).map_err(Failure::Parse)?;
+    let mut interpreter = session.interpreter(&statements);
+
+    for statement in statements.iter() {
+        match statement {
+            Statement::Expr(expr) => match expr.eval(&mut interpreter).map_err(Failure::Runtime)? {
+                Data::TempNil => (),
+                value => {
+                    let _ = writeln!(interpreter.stdout, "{}", value);
+                }
+            },
+            _ => statement.exec(&mut interpreter).map_err(Failure::Runtime)?
+        }
+    }
+
+    Ok(())
+}
+
+fn repl(config: &Config) -> Result<ExitCode, Failure> {
+    let mut session = Session::new(config)?;
+
+    println!("oxid {} repl, press Ctrl-D to exit", env!("CARGO_PKG_VERSION"));
+    panic::set_hook(Box::new(|info| {
+        eprintln!("internal error: {}", panic_message(info.payload()));
+    }));
+
+    loop {
+        let mut input = String::new();
+
+        loop {
+            print!("{}", if input.is_empty() { "> " } else { "... " });
+            io::stdout().flush().unwrap();
+
+            // stdin isn't locked between lines so scripts can read from it with `read_line`
+            match io::stdin().read_line(&mut input) {
+                Ok(0) | Err(_) => return Ok(exit_code::SUCCESS.into()),
+                Ok(_) => ()
+            }
+
+            if open_brackets(&input) <= 0 {
+                break;
+            }
+        }
+
+        let input = input.trim();
+        if input.is_empty() {
+            continue;
+        }
+
+        let buf = match input.ends_with(';') || input.ends_with('}') {
+            true => input.to_string(),
+            false => format!("{};", input)
+        };
+
+        // the registry and aliases are moved into the parser, so a panic while parsing would
+        // lose them instead of leaving changes to roll back
+        let (modules, aliases) = (session.modules.clone(), session.aliases.clone());
+        session.sim_memory.checkpoint();
+        session.memory.checkpoint();
+
+        let res = panic::catch_unwind(AssertUnwindSafe(|| eval_repl_input(&mut session, &buf)));
+
+        match res {
+            Ok(Ok(())) => {
+                session.sim_memory.commit();
+                session.memory.commit();
+                continue;
+            },
+            Ok(Err(Failure::Runtime(RuntimeErr::Exit(code)))) => return Err(Failure::Runtime(RuntimeErr::Exit(code))),
+            Ok(Err(failure)) => {
+                failure.report();
+            },
+            Err(_) => ()
+        }
+
+        // anything the failed input declared is forgotten, including globals it redeclared
+        (session.modules, session.aliases) = (modules, aliases);
+        session.sim_memory.rollback();
+        session.memory.rollback();
+    }
+}
+
+/// Stack reserved for every nested call the tree walker may make, on top of `BASE_STACK_SIZE`.
+/// Nested blocks and expressions in a function body take a few kilobytes each in debug builds
+const STACK_PER_CALL: usize = 64 * 1024;
+const BASE_STACK_SIZE: usize = 8 * 1024 * 1024;
+
 fn main() -> ExitCode {
-    let mut args = env::args();
-    let config = Config::new(&mut args);
+    let config = match Config::new(&mut env::args()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            return exit_code::USAGE_ERR.into();
+        }
+    };
 
-    let mut sim_memory = Memory::new();
-    BuiltinFn::populate_sim_memory(&mut sim_memory);
+    // the stack grows with `--max-depth` so running out of it is reported as `CallDepthExceeded`
+    // instead of aborting, the memory is only reserved until a deep recursion actually uses it
+    let max_depth = config.limits.max_depth.unwrap_or(Limits::DEFAULT_MAX_DEPTH);
+    let stack_size = max_depth.saturating_mul(STACK_PER_CALL).saturating_add(BASE_STACK_SIZE);
 
-    let mut memory = Memory::new();
-    BuiltinFn::populate_memory(&mut memory);
+    match thread::Builder::new().stack_size(stack_size).spawn(move || run_command(&config)) {
+        // a panic has already been reported by the panic hook
+        Ok(handle) => handle.join().unwrap_or(exit_code::INTERNAL_ERR.into()),
+        Err(err) => {
+            eprintln!("error: failed to reserve a stack for `--max-depth {}`: {}", max_depth, err);
+            exit_code::USAGE_ERR.into()
+        }
+    }
+}
 
-    let std_file = config.std_file
-        .unwrap_or(String::from("std.ox"));
-    execute_file(&std_file[..], &mut sim_memory, &mut memory);
-    execute_file(&config.source_file[..], &mut sim_memory, &mut memory);
+fn run_command(config: &Config) -> ExitCode {
+    let res = match config.command {
+        Command::Help => {
+            println!("{}", USAGE);
+            Ok(exit_code::SUCCESS.into())
+        },
+        Command::Version => {
+            println!("oxid {}", env!("CARGO_PKG_VERSION"));
+            Ok(exit_code::SUCCESS.into())
+        },
+        Command::Run | Command::Check | Command::Test if config.dump_tokens || config.dump_ast.is_some() => dump(config),
+        Command::Run => run(config),
+        Command::Check => check(config),
+        Command::Fmt => fmt(config),
+        Command::Repl => repl(config),
+        Command::Test => test(config)
+    };
 
-    0.into()
+    res.unwrap_or_else(|failure| failure.report())
 }
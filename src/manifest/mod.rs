@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+pub const MANIFEST_FILENAME: &str = "Oxid.toml";
+
+/// A project's `Oxid.toml`
+///
+/// ```toml
+/// [package]
+/// name = "app"
+/// entry = "main.ox"
+///
+/// [dependencies]
+/// helpers = { path = "../helpers" }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct Manifest {
+    pub package: Package,
+    #[serde(default)]
+    pub dependencies: HashMap<String, Dependency>,
+    /// Directory containing the manifest, relative paths in the manifest are resolved against it
+    #[serde(skip)]
+    pub dir: PathBuf
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Package {
+    pub name: String,
+    /// File run by `oxid run`
+    #[serde(default = "default_entry")]
+    pub entry: String,
+    /// File imported when the package is used as a dependency
+    #[serde(default = "default_lib")]
+    pub lib: String
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Dependency {
+    /// Either a `.ox` file or a directory containing another package
+    pub path: String
+}
+
+fn default_entry() -> String {
+    String::from("main.ox")
+}
+
+fn default_lib() -> String {
+    String::from("lib.ox")
+}
+
+impl Manifest {
+    pub fn load(manifest_path: &Path) -> Result<Manifest, String> {
+        let buf = fs::read_to_string(manifest_path)
+            .map_err(|err| format!("failed to read `{}`: {}", manifest_path.display(), err))?;
+
+        let mut manifest: Manifest = toml::from_str(&buf)
+            .map_err(|err| format!("invalid manifest `{}`: {}", manifest_path.display(), err))?;
+        manifest.dir = manifest_path.parent().unwrap_or(Path::new("")).to_path_buf();
+
+        Ok(manifest)
+    }
+
+    /// Looks for a manifest in `start_dir` and each of its parent directories
+    pub fn find(start_dir: &Path) -> Option<PathBuf> {
+        start_dir.ancestors()
+            .map(|dir| dir.join(MANIFEST_FILENAME))
+            .find(|manifest_path| manifest_path.is_file())
+    }
+
+    pub fn entry_path(&self) -> PathBuf {
+        self.dir.join(&self.package.entry)
+    }
+
+    /// Maps the canonical directory of this package and of every package it depends on, directly
+    /// or not, to the dependencies that package declares. Each of those maps a name to the file
+    /// imported by `mod <name>;` from within the package, so a package can only import what it
+    /// depends on itself.
+    pub fn resolve_dependencies(&self) -> Result<HashMap<PathBuf, HashMap<String, PathBuf>>, String> {
+        let mut resolved = HashMap::new();
+        self.collect_dependencies(&mut resolved)?;
+
+        Ok(resolved)
+    }
+
+    fn collect_dependencies(&self, resolved: &mut HashMap<PathBuf, HashMap<String, PathBuf>>) -> Result<(), String> {
+        let package_dir = canonical_dir(&self.dir);
+        if resolved.contains_key(&package_dir) {
+            return Ok(());
+        }
+        // claimed before visiting the dependencies so packages depending on each other terminate
+        resolved.insert(package_dir.clone(), HashMap::new());
+
+        let mut package_dependencies = HashMap::new();
+        let mut dependency_manifests = vec![];
+
+        for (name, dependency) in self.dependencies.iter() {
+            let dependency_path = self.dir.join(&dependency.path);
+
+            let module_file = if dependency_path.extension().is_some_and(|ext| ext == "ox") {
+                dependency_path
+            } else {
+                let manifest_path = dependency_path.join(MANIFEST_FILENAME);
+
+                if manifest_path.is_file() {
+                    let manifest = Manifest::load(&manifest_path)?;
+                    let lib_path = manifest.dir.join(&manifest.package.lib);
+
+                    dependency_manifests.push(manifest);
+                    lib_path
+                } else {
+                    // a package without a manifest has no dependencies of its own
+                    resolved.entry(canonical_dir(&dependency_path)).or_default();
+                    dependency_path.join(default_lib())
+                }
+            };
+
+            if !module_file.is_file() {
+                return Err(format!("dependency `{}` not found at `{}`", name, module_file.display()));
+            }
+
+            package_dependencies.insert(name.to_string(), module_file);
+        }
+        resolved.insert(package_dir, package_dependencies);
+
+        for manifest in dependency_manifests {
+            manifest.collect_dependencies(resolved)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn canonical_dir(dir: &Path) -> PathBuf {
+    fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::statements::module::ModuleRegistry;
+
+    use super::Manifest;
+
+    #[test]
+    fn resolve_project_dependencies() {
+        let dir = std::env::temp_dir().join(format!("oxid-test-manifest-{}", std::process::id()));
+        let files = [
+            ("app/Oxid.toml", "[package]\nname = \"app\"\nentry = \"src/main.ox\"\n\n[dependencies]\nhelpers = { path = \"../helpers\" }\nutil = { path = \"../app_util.ox\" }\n"),
+            ("app/src/main.ox", "mod helpers;"),
+            ("app_util.ox", ""),
+            ("helpers/Oxid.toml", "[package]\nname = \"helpers\"\n\n[dependencies]\nshared = { path = \"../shared.ox\" }\nutil = { path = \"../helpers_util.ox\" }\n"),
+            ("helpers/lib.ox", "mod shared;"),
+            ("helpers_util.ox", ""),
+            ("shared.ox", ""),
+            ("libs/extra.ox", "")
+        ];
+        for (filename, buf) in files {
+            let file_path = dir.join(filename);
+            fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+            fs::write(file_path, buf).unwrap();
+        }
+
+        let manifest_path = Manifest::find(&dir.join("app/src")).unwrap();
+        assert_eq!(manifest_path, dir.join("app/Oxid.toml"));
+
+        let manifest = Manifest::load(&manifest_path).unwrap();
+        assert_eq!(manifest.package.name, "app");
+        assert_eq!(manifest.entry_path(), dir.join("app/src/main.ox"));
+
+        let mut modules = ModuleRegistry::default();
+        modules.dependencies = manifest.resolve_dependencies().unwrap();
+        modules.search_paths = vec![dir.join("libs")];
+        let entry = manifest.entry_path().to_string_lossy().to_string();
+        let helpers_lib = dir.join("helpers/lib.ox").to_string_lossy().to_string();
+
+        let find = |importer: &str, name| {
+            modules.find_module_file(importer, name).map(|file_path| fs::canonicalize(file_path).unwrap())
+        };
+        let dir = fs::canonicalize(dir).unwrap();
+
+        assert_eq!(find(&entry, "helpers"), Some(dir.join("helpers/lib.ox")));
+        assert_eq!(find(&entry, "util"), Some(dir.join("app_util.ox")));
+        assert_eq!(find(&entry, "extra"), Some(dir.join("libs/extra.ox")));
+        assert_eq!(find(&entry, "missing"), None);
+
+        // dependencies of a dependency are only visible to it, under its own names
+        assert_eq!(find(&entry, "shared"), None);
+        assert_eq!(find(&helpers_lib, "shared"), Some(dir.join("shared.ox")));
+        assert_eq!(find(&helpers_lib, "util"), Some(dir.join("helpers_util.ox")));
+        assert_eq!(find(&helpers_lib, "helpers"), None);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn missing_package_is_an_error() {
+        let dir = std::env::temp_dir().join(format!("oxid-test-bad-manifest-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Oxid.toml"), "[dependencies]\n").unwrap();
+
+        let err = Manifest::load(&dir.join("Oxid.toml")).unwrap_err();
+        assert!(err.contains("invalid manifest"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,42 +1,232 @@
 use std::collections::HashMap;
 
+/// Where a variable was found to be declared by the resolver, see [`crate::resolver`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Slot {
+    /// Declared `depth` scopes out from the innermost one, as the `index`th name of that scope
+    Local { depth: usize, index: usize },
+    /// Declared in the global scope, which is still looked up by name
+    Global
+}
+
+/// Names declared in a scope, stored in the order they were first declared so they can also be
+/// accessed by index
+#[derive(Debug, Clone)]
+pub struct Scope<T> {
+    names: HashMap<String, usize>,
+    values: Vec<T>,
+    /// Declarations since the last [`Scope::checkpoint`], if there is one
+    journal: Option<Journal<T>>
+}
+
+/// What a scope needs to go back to a checkpoint
+#[derive(Debug, Clone)]
+struct Journal<T> {
+    /// Number of names declared at the checkpoint
+    len: usize,
+    /// Names declared since, in order
+    added: Vec<String>,
+    /// Values of names declared before the checkpoint that were replaced by redeclaring them,
+    /// with their index
+    replaced: Vec<(usize, T)>
+}
+
+impl<T> Default for Scope<T> {
+    fn default() -> Self {
+        Self {
+            names: HashMap::new(),
+            values: vec![],
+            journal: None
+        }
+    }
+}
+
+impl<T> Scope<T> {
+    /// Declares `key`, redeclaring a name keeps its index
+    pub fn insert(&mut self, key: String, value: T) {
+        match self.names.get(&key) {
+            Some(&index) => self.replace(index, value),
+            None => {
+                if let Some(journal) = &mut self.journal {
+                    journal.added.push(key.to_string());
+                }
+
+                self.names.insert(key, self.values.len());
+                self.values.push(value);
+            }
+        }
+    }
+
+    /// Declares `key` at the index the resolver gave it
+    pub fn insert_at(&mut self, index: usize, key: String, value: T) {
+        if index < self.values.len() {
+            debug_assert_eq!(self.names.get(&key), Some(&index), "`{}` was resolved to the wrong slot", key);
+            self.replace(index, value);
+        } else {
+            debug_assert_eq!(index, self.values.len(), "`{}` was resolved to the wrong slot", key);
+            self.insert(key, value);
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&T> {
+        self.names.get(key).map(|&index| &self.values[index])
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut T> {
+        self.names.get(key).map(|&index| &mut self.values[index])
+    }
+
+    pub fn index_of(&self, key: &str) -> Option<usize> {
+        self.names.get(key).copied()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.names.keys()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.values.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Redeclares the name at `index`, keeping the old value if the checkpoint needs it
+    fn replace(&mut self, index: usize, value: T) {
+        let old = std::mem::replace(&mut self.values[index], value);
+
+        match &mut self.journal {
+            Some(journal) if index < journal.len => journal.replaced.push((index, old)),
+            _ => ()
+        }
+    }
+
+    /// Starts keeping track of declarations so [`Scope::rollback`] can undo them. Changing the
+    /// value of an existing name in place isn't a declaration and is kept.
+    pub fn checkpoint(&mut self) {
+        self.journal = Some(Journal { len: self.values.len(), added: vec![], replaced: vec![] });
+    }
+
+    /// Forgets the names declared since the checkpoint and gives redeclared names back their
+    /// old value
+    pub fn rollback(&mut self) {
+        let Some(journal) = self.journal.take() else {
+            return;
+        };
+
+        for (index, old) in journal.replaced.into_iter().rev() {
+            self.values[index] = old;
+        }
+        for key in journal.added.iter() {
+            self.names.remove(key);
+        }
+        self.values.truncate(journal.len);
+    }
+
+    /// Keeps everything declared since the checkpoint
+    pub fn commit(&mut self) {
+        self.journal = None;
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Memory<T> {
-    pub scopes: Vec<HashMap<String, T>>
+    pub scopes: Vec<Scope<T>>
+}
+
+impl<T> Default for Memory<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<T> Memory<T> {
     pub fn new() -> Self {
         Self {
-            scopes: vec![HashMap::new()]
+            scopes: vec![Scope::default()]
         }
     }
 
-    pub fn insert(&mut self, key: String, value: T) -> Option<T> {
-        self.scopes.last_mut().unwrap().insert(key, value)
+    pub fn insert(&mut self, key: String, value: T) {
+        self.scopes.last_mut().unwrap().insert(key, value);
     }
 
+    /// Replaces the value of the innermost variable called `key`, like `get` finds
     pub fn assign(&mut self, key: String, value: T) -> Option<T> {
-        for scope in self.scopes.iter_mut() {
-            if scope.contains_key(&key) {
-                return scope.insert(key, value);
-            }
-        }
-
-        None
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(&key))
+            .map(|slot| std::mem::replace(slot, value))
     }
 
-    pub fn get(&self, key: &String) -> Option<&T> {
+    pub fn get(&self, key: &str) -> Option<&T> {
         self.scopes
             .iter()
             .rev()
             .find_map(|scope| scope.get(key))
     }
 
-    pub fn has(&self, key: &String) -> bool {
+    /// Like `get` but ignores the outermost, global scope
+    pub fn get_local(&self, key: &str) -> Option<&T> {
+        self.scopes[1..]
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(key))
+    }
+
+    pub fn has(&self, key: &str) -> bool {
         self.get(key).is_some()
     }
 
+    /// Like `insert` but goes straight to the slot the resolver found, if any
+    pub fn insert_at(&mut self, key: String, value: T, slot: Option<Slot>) {
+        match slot {
+            Some(Slot::Local { depth: 0, index }) => self.scopes.last_mut().unwrap().insert_at(index, key, value),
+            _ => self.insert(key, value)
+        }
+    }
+
+    /// Like `assign` but goes straight to the slot the resolver found, if any
+    pub fn assign_at(&mut self, key: &str, value: T, slot: Option<Slot>) -> Option<T> {
+        match slot {
+            Some(_) => self.get_at_mut(key, slot).map(|old| std::mem::replace(old, value)),
+            None => self.assign(key.to_string(), value)
+        }
+    }
+
+    /// Like `get` but goes straight to the slot the resolver found, if any
+    pub fn get_at(&self, key: &str, slot: Option<Slot>) -> Option<&T> {
+        match slot {
+            Some(Slot::Local { depth, index }) => {
+                let scope = &self.scopes[self.scopes.len() - 1 - depth];
+                debug_assert_eq!(scope.index_of(key), Some(index), "`{}` was resolved to the wrong slot", key);
+                scope.values.get(index)
+            },
+            Some(Slot::Global) => self.scopes[0].get(key),
+            None => self.get(key)
+        }
+    }
+
+    /// Like `get_at` but for changing the value in place
+    pub fn get_at_mut(&mut self, key: &str, slot: Option<Slot>) -> Option<&mut T> {
+        match slot {
+            Some(Slot::Local { depth, index }) => {
+                let scope_index = self.scopes.len() - 1 - depth;
+                let scope = &mut self.scopes[scope_index];
+                debug_assert_eq!(scope.index_of(key), Some(index), "`{}` was resolved to the wrong slot", key);
+                scope.values.get_mut(index)
+            },
+            Some(Slot::Global) => self.scopes[0].get_mut(key),
+            None => self.scopes.iter_mut().rev().find_map(|scope| scope.get_mut(key))
+        }
+    }
+
     pub fn scoped<F>(&mut self, f: F)
     where
         F: FnOnce()
@@ -49,10 +239,26 @@ impl<T> Memory<T> {
     }
 
     pub fn push_scope(&mut self) {
-        self.scopes.push(HashMap::new());
+        self.scopes.push(Scope::default());
+    }
+
+    pub fn pop_scope(&mut self) -> Scope<T> {
+        self.scopes.pop().unwrap()
+    }
+
+    /// Starts keeping track of what's declared in the global scope, see [`Scope::checkpoint`]
+    pub fn checkpoint(&mut self) {
+        self.scopes[0].checkpoint();
+    }
+
+    /// Drops the scopes left open and undoes the global declarations since the checkpoint
+    pub fn rollback(&mut self) {
+        self.scopes.truncate(1);
+        self.scopes[0].rollback();
     }
 
-    pub fn pop_scope(&mut self) {
-        self.scopes.pop().unwrap();
+    /// Keeps the global declarations since the checkpoint
+    pub fn commit(&mut self) {
+        self.scopes[0].commit();
     }
 }
@@ -1,3 +1,5 @@
+use std::rc::Rc;
+
 use crate::data::Data;
 use crate::types::Type;
 
@@ -18,7 +20,7 @@ pub fn typ(lhs: &Type, rhs: &Type) -> Option<Type> {
 pub fn op(lhs: &Data, rhs: &Data) -> Option<Data> {
     match lhs {
         Data::String(lhs) => match rhs {
-            Data::String(rhs) => Some(Data::String(lhs.clone() + rhs)),
+            Data::String(rhs) => Some(Data::String(Rc::new(lhs.to_string() + rhs))),
             _ => None
         },
         Data::Number(lhs) => match rhs {
@@ -29,6 +31,17 @@ pub fn op(lhs: &Data, rhs: &Data) -> Option<Data> {
     }
 }
 
+/// Adds `rhs` to `lhs` in place, a string is appended to without being copied unless it's shared
+pub fn op_assign(lhs: &mut Data, rhs: &Data) -> Option<()> {
+    match (lhs, rhs) {
+        (Data::String(lhs), Data::String(rhs)) => Rc::make_mut(lhs).push_str(rhs),
+        (Data::Number(lhs), Data::Number(rhs)) => *lhs += rhs,
+        _ => return None
+    }
+
+    Some(())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::types::Type;
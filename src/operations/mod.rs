@@ -1,4 +1,3 @@
-use crate::errors::ParseErrKind;
 use crate::data::Data;
 use crate::types::Type;
 
@@ -59,4 +58,14 @@ impl Operation {
 
         res.expect("invalid operation slipped through to the interpreter after a valid operation check")
     }
+
+    /// Like `op` but updates `lhs` in place, only addition can be assigned with so far
+    pub fn op_assign(&self, lhs: &mut Data, rhs: &Data) {
+        let res = match self {
+            Operation::Add => add::op_assign(lhs, rhs),
+            _ => None
+        };
+
+        res.expect("invalid operation slipped through to the interpreter after a valid operation check")
+    }
 }
@@ -0,0 +1,225 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::data::{Data, ExprLiteral};
+use crate::expressions::Expr;
+use crate::expressions::block::ExprBlock;
+use crate::statements::Statement;
+use crate::visitor::{self, Visitor};
+
+/// How much a parsed program is rewritten before it runs, set with `--opt-level`
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub enum OptLevel {
+    /// Runs the program as it was written
+    #[default]
+    None,
+    /// Folds operations on constants and removes `if` bodies that can never run
+    Basic,
+    /// Also inlines immutable variables that are initialized to a constant
+    Full
+}
+
+impl OptLevel {
+    pub fn from_number(level: &str) -> Option<OptLevel> {
+        match level {
+            "0" => Some(OptLevel::None),
+            "1" => Some(OptLevel::Basic),
+            "2" => Some(OptLevel::Full),
+            _ => None
+        }
+    }
+}
+
+/// Rewrites a parsed program into one that does the same in fewer steps, it has to have been
+/// resolved already as variables keep their slots
+pub fn optimize(statements: &mut [Statement], level: OptLevel) {
+    if level == OptLevel::None {
+        return;
+    }
+
+    // whether a variable is declared `mut` isn't checked, so anything assigned to anywhere is
+    // never treated as a constant
+    let mut assigned = AssignedNames(HashSet::new());
+    if level >= OptLevel::Full {
+        for statement in statements.iter() {
+            assigned.visit_statement(statement);
+        }
+    }
+
+    let mut optimizer = Optimizer {
+        level,
+        assigned: assigned.0,
+        scopes: vec![HashMap::new()],
+        function_scope: 0
+    };
+
+    for statement in statements.iter_mut() {
+        optimizer.statement(statement);
+    }
+}
+
+struct Optimizer {
+    level: OptLevel,
+    /// Names assigned to somewhere in the program
+    assigned: HashSet<String>,
+    /// Variables declared in each scope, with their value if it's a constant
+    scopes: Vec<HashMap<String, Option<Data>>>,
+    /// Index of the outermost scope of the function being optimized, variables declared
+    /// outside of it could have been redeclared by the time it's called
+    function_scope: usize
+}
+
+impl Optimizer {
+    fn declare(&mut self, name: &str, constant: Option<Data>) {
+        self.scopes.last_mut().unwrap().insert(name.to_string(), constant);
+    }
+
+    fn constant(&self, name: &str) -> Option<Data> {
+        if self.assigned.contains(name) {
+            return None;
+        }
+
+        self.scopes[self.function_scope..].iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .cloned()
+            .flatten()
+    }
+
+    fn scoped<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut Self)
+    {
+        self.scopes.push(HashMap::new());
+        f(self);
+        self.scopes.pop();
+    }
+
+    fn statement(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::VariableAssignment(var_assign) => {
+                self.expr(&mut var_assign.init_value);
+
+                let constant = match &var_assign.init_value {
+                    Expr::Literal(literal_expr) if !var_assign.is_mut && self.level >= OptLevel::Full => {
+                        Some(literal_expr.data.clone())
+                    },
+                    _ => None
+                };
+                self.declare(&var_assign.name, constant);
+            },
+            Statement::FunctionDeclaration(fn_decl) => {
+                let outer_function_scope = self.function_scope;
+                self.function_scope = self.scopes.len();
+
+                let (args, body) = (&fn_decl.args, &mut fn_decl.body);
+                self.scoped(|optimizer| {
+                    for (arg_name, _) in args.iter() {
+                        optimizer.declare(arg_name, None);
+                    }
+                    optimizer.block(body);
+                });
+
+                self.function_scope = outer_function_scope;
+                self.declare(&fn_decl.name, None);
+            },
+            Statement::ModuleImport(mod_import) => {
+                for statement in mod_import.statements.iter_mut() {
+                    self.statement(statement);
+                }
+            },
+            Statement::UseDeclaration(_) => (),
+            Statement::Expr(expr) => self.expr(expr)
+        }
+    }
+
+    fn block(&mut self, block: &mut ExprBlock) {
+        for statement in block.body.iter_mut() {
+            self.statement(statement);
+        }
+    }
+
+    fn expr(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Literal(_) => (),
+            Expr::Binary(binary_expr) => {
+                self.expr(&mut binary_expr.lhs);
+                self.expr(&mut binary_expr.rhs);
+
+                if let (Expr::Literal(lhs), Expr::Literal(rhs)) = (binary_expr.lhs.as_ref(), binary_expr.rhs.as_ref()) {
+                    let data = binary_expr.operation.op(&lhs.data, &rhs.data);
+                    *expr = Expr::Literal(ExprLiteral::new(data));
+                }
+            },
+            Expr::Index(index_expr) => {
+                self.expr(&mut index_expr.index);
+                self.expr(&mut index_expr.value);
+            },
+            Expr::Unary(unary_expr) => self.expr(&mut unary_expr.value),
+            Expr::Field(field_expr) => {
+                if field_expr.child.is_some() {
+                    return;
+                }
+
+                if let Some(data) = self.constant(&field_expr.field_name) {
+                    *expr = Expr::Literal(ExprLiteral::new(data));
+                }
+            },
+            Expr::Call(call_expr) => {
+                self.expr(&mut call_expr.path);
+                for arg_expr in call_expr.args.iter_mut() {
+                    self.expr(arg_expr);
+                }
+            },
+            Expr::Assign(assign_expr) => {
+                // the variable assigned to is left alone, it's never a constant
+                if let Expr::Member(member_expr) = assign_expr.lhs.as_mut() {
+                    self.expr(&mut member_expr.value);
+                }
+                self.expr(&mut assign_expr.rhs);
+            },
+            Expr::Block(block_expr) => self.block(block_expr),
+            Expr::For(for_expr) => {
+                self.expr(&mut for_expr.start_i);
+                self.expr(&mut for_expr.end_i);
+
+                let (index_var, body) = (&for_expr.index_var, &mut for_expr.body);
+                self.scoped(|optimizer| {
+                    optimizer.declare(index_var, None);
+                    optimizer.block(body);
+                });
+            },
+            Expr::If(if_expr) => {
+                self.expr(&mut if_expr.condition_expr);
+
+                if let Expr::Literal(ExprLiteral { data: Data::Bool(false) }) = if_expr.condition_expr.as_ref() {
+                    *expr = Expr::Literal(ExprLiteral::new(Data::TempNil));
+                    return;
+                }
+
+                let body = &mut if_expr.body;
+                self.scoped(|optimizer| optimizer.block(body));
+            },
+            Expr::List(list_expr) => {
+                for item in list_expr.items.iter_mut() {
+                    self.expr(item);
+                }
+            },
+            Expr::Member(member_expr) => self.expr(&mut member_expr.value)
+        }
+    }
+}
+
+/// Collects the names of every variable assigned to
+struct AssignedNames(HashSet<String>);
+
+impl Visitor for AssignedNames {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let Expr::Assign(assign_expr) = expr {
+            if let Some(path) = assign_expr.lhs.mangle_path() {
+                self.0.insert(path);
+            }
+        }
+
+        visitor::walk_expr(self, expr);
+    }
+}
@@ -1,6 +1,11 @@
-use crate::memory::Memory;
+use std::collections::HashMap;
+
+use crate::memory::{Memory, Scope};
+use crate::resolver;
+use crate::sandbox::Capabilities;
 use crate::tokenizer::{token::Token, token_type::TokenType};
 use crate::statements::Statement;
+use crate::statements::module::ModuleRegistry;
 use crate::errors::{ParseErrKind, ParseErr};
 use crate::types::Type;
 
@@ -10,14 +15,143 @@ mod token_collectior;
 
 pub struct Parser<'a, 'm> {
     pub collector: TokenCollector<'a>,
-    pub sim_memory: &'m mut Memory<Type>
+    pub sim_memory: &'m mut Memory<Type>,
+    /// Path of the module being parsed, like `foo::bar`, empty for the entry file
+    pub module_path: String,
+    /// Names brought into scope with `use`, mapped to the path they refer to
+    pub aliases: Scope<String>,
+    pub modules: ModuleRegistry,
+    /// What the program is allowed to do, denied builtins and imports are reported as errors
+    pub capabilities: Capabilities,
+    /// Types registered by the embedding application, usable in type annotations
+    pub host_types: HashMap<String, Type>,
+    /// Errors recovered from so far, including those of imported modules
+    pub errors: Vec<ParseErr>
 }
 
 impl<'a, 'm> Parser<'a, 'm> {
     pub fn new(collector: TokenCollector<'a>, sim_memory: &'m mut Memory<Type>) -> Self {
         Self {
             collector,
-            sim_memory
+            sim_memory,
+            module_path: String::new(),
+            aliases: Scope::default(),
+            modules: ModuleRegistry::default(),
+            capabilities: Capabilities::default(),
+            host_types: HashMap::new(),
+            errors: vec![]
+        }
+    }
+
+    /// Resolves a type annotation, like `Number` or a registered host type
+    pub fn type_from_name(&self, name: &str) -> Option<Type> {
+        Type::from_name(name).or_else(|| self.host_types.get(name).cloned())
+    }
+
+    /// Prefixes `name` with the path of the module being parsed
+    pub fn mangle(&self, name: &str) -> String {
+        if self.module_path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}::{}", self.module_path, name)
+        }
+    }
+
+    /// Declares `name` in the current scope, items declared at the top level of a module are
+    /// mangled with the module path and private until marked `pub`. Returns the name the item
+    /// is stored under.
+    pub fn declare(&mut self, name: &str, typ: Type) -> String {
+        if self.sim_memory.scopes.len() > 1 {
+            self.sim_memory.insert(name.to_string(), typ);
+            return name.to_string();
+        }
+
+        let mangled = self.mangle(name);
+        self.sim_memory.insert(mangled.to_string(), typ);
+        self.modules.set_private(&mangled, true);
+
+        mangled
+    }
+
+    /// Resolves a path as written in the source, like `x` or `foo::bar`, to the mangled name it
+    /// is stored under
+    pub fn resolve_path(&self, path: &str) -> Option<String> {
+        if self.is_local(path) {
+            return Some(path.to_string());
+        }
+
+        let mangled = self.modules.canonicalize_path(&self.mangle_written_path(path));
+
+        if self.sim_memory.has(&mangled) {
+            return Some(mangled);
+        }
+
+        if let Some(prelude_item) = self.modules.prelude.get(path) {
+            return Some(prelude_item.to_string());
+        }
+
+        // builtins are available in every module without a path
+        match self.sim_memory.get(path) {
+            Some(Type::BuiltinFn(_)) => Some(path.to_string()),
+            _ => None
+        }
+    }
+
+    /// Whether the path as written in the source is accessible from the module being parsed,
+    /// private items and modules can only be used from within the module that declares them
+    pub fn is_visible(&self, path: &str) -> bool {
+        if self.is_local(path) {
+            return true;
+        }
+
+        let mangled = self.mangle_written_path(path);
+        let canonical = self.modules.canonicalize_path(&mangled);
+
+        // every module along the path as written has to be visible, while the item itself is
+        // only private to the module it was originally declared in
+        let mut item_path = String::new();
+        for segment in mangled.split("::") {
+            let owner = item_path.to_string();
+
+            if !item_path.is_empty() {
+                item_path.push_str("::");
+            }
+            item_path.push_str(segment);
+
+            if self.modules.private_items.contains(&item_path) && !self.is_within(&owner) {
+                return false;
+            }
+        }
+
+        let canonical_owner = canonical.rsplit_once("::").map_or("", |(owner, _)| owner);
+        !self.modules.private_items.contains(&canonical) || self.is_within(canonical_owner)
+    }
+
+    fn is_local(&self, path: &str) -> bool {
+        !path.contains("::") && self.sim_memory.get_local(path).is_some()
+    }
+
+    /// Whether the module being parsed is `module_path` or one of its submodules
+    fn is_within(&self, module_path: &str) -> bool {
+        module_path.is_empty()
+            || self.module_path == module_path
+            || self.module_path.starts_with(&format!("{}::", module_path))
+    }
+
+    /// Applies `use` aliases and the current module path to a path as written in the source
+    pub fn mangle_written_path(&self, path: &str) -> String {
+        let (first, rest) = match path.split_once("::") {
+            Some((first, rest)) => (first, Some(rest)),
+            None => (path, None)
+        };
+
+        let first_mangled = self.aliases.get(first)
+            .cloned()
+            .unwrap_or_else(|| self.mangle(first));
+
+        match rest {
+            Some(rest) => format!("{}::{}", first_mangled, rest),
+            None => first_mangled
         }
     }
 
@@ -26,21 +160,74 @@ impl<'a, 'm> Parser<'a, 'm> {
             .from_token(token)
     }
 
+    /// Parses and type checks the whole program, reporting the first error
     pub fn generate_program(&mut self) -> Vec<Statement> {
+        self.parse_program().unwrap_or_else(|errs| errs[0].report())
+    }
+
+    /// Parses and type checks the whole program, skipping to the next statement after an error
+    /// so that every error is collected instead of only the first
+    pub fn parse_program(&mut self) -> Result<Vec<Statement>, Vec<ParseErr>> {
         let mut statements = vec![];
 
         for _ in 0..1_000_000 {
             let next_token = self.collector.next();
-            match next_token.token {
-                TokenType::EOF => return statements,
-                _ => {
-                    let statement = Statement::parse_statement(self, next_token).unwrap_or_else(|err| err.report());
-                    statements.push(statement);
+            if let TokenType::EOF = next_token.token {
+                return match self.errors.is_empty() {
+                    true => {
+                        resolver::resolve(&mut statements);
+                        Ok(statements)
+                    },
+                    false => Err(std::mem::take(&mut self.errors))
+                };
+            }
+
+            let start_index = self.collector.index.unwrap();
+            let scope_count = self.sim_memory.scopes.len();
+
+            match Statement::parse_statement(self, next_token) {
+                Ok(statement) => statements.push(statement),
+                Err(err) => {
+                    self.errors.push(err);
+                    self.sim_memory.scopes.truncate(scope_count);
+                    self.skip_statement(start_index);
                 }
             }
         }
 
         panic!("loop never breaked");
     }
-}
 
+    /// Moves past the end of the statement starting at `start_index`, which is the first
+    /// semicolon or the closing curly of its outermost block not followed by an `else`
+    fn skip_statement(&mut self, start_index: usize) {
+        self.collector.index = Some(start_index);
+        let mut token = self.collector.current();
+        let mut depth = 0;
+
+        loop {
+            match token.token {
+                TokenType::EOF => {
+                    self.collector.back();
+                    return;
+                },
+                TokenType::LeftCurly => depth += 1,
+                TokenType::RightCurly if depth <= 1 => {
+                    match self.collector.next().token {
+                        TokenType::Semicolon => return,
+                        TokenType::Else => depth = 0,
+                        _ => {
+                            self.collector.back();
+                            return;
+                        }
+                    }
+                },
+                TokenType::RightCurly => depth -= 1,
+                TokenType::Semicolon if depth == 0 => return,
+                _ => ()
+            }
+
+            token = self.collector.next();
+        }
+    }
+}
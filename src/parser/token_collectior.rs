@@ -13,6 +13,7 @@ impl<'a> TokenCollector<'a> {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> &'a Token {
         if let Some(index) = self.index {
             self.index = Some(index + 1);
@@ -20,7 +21,7 @@ impl<'a> TokenCollector<'a> {
             self.index = Some(0);
         }
 
-        &self.tokens.get(self.index.unwrap()).expect("try to call next after EOF")
+        self.tokens.get(self.index.unwrap()).expect("try to call next after EOF")
     }
 
     // NOTE if you're able to not use this function by for example adding a `next_token` argument
@@ -34,12 +35,12 @@ impl<'a> TokenCollector<'a> {
             self.index = None;
         }
 
-        Some(&self.tokens.get(self.index.unwrap()).expect("try to call back after EOF"))
+        Some(self.tokens.get(self.index.unwrap()).expect("try to call back after EOF"))
     }
 
     // ! only for use with error messages
     pub fn current(&self) -> &'a Token {
-        &self.tokens.get(self.index.unwrap()).unwrap()
+        self.tokens.get(self.index.unwrap()).unwrap()
     }
 }
 
@@ -0,0 +1,162 @@
+use crate::expressions::Expr;
+use crate::expressions::block::ExprBlock;
+use crate::memory::Slot;
+use crate::statements::Statement;
+
+/// Works out where every variable used in a parsed program lives, so that at runtime it can be
+/// read straight from its slot instead of searching every scope by name.
+///
+/// Scopes are pushed and names declared in the same order as when the program runs, so a name
+/// declared within the current function can be found a fixed number of scopes out at a fixed
+/// index. Any other name used within a function is left unresolved and looked up by name like
+/// before, as a function sees the variables of whoever calls it and those depend on where it's
+/// called from. Only outside of functions is such a name known to be a global.
+pub fn resolve(statements: &mut [Statement]) {
+    let mut resolver = Resolver {
+        scopes: vec![vec![]],
+        function_scope: 0
+    };
+
+    for statement in statements.iter_mut() {
+        resolver.statement(statement);
+    }
+}
+
+struct Resolver {
+    /// Names declared in each local scope in order, the global scope is left empty as it's
+    /// always looked up by name
+    scopes: Vec<Vec<String>>,
+    /// Index of the outermost scope of the function being resolved, 0 outside of functions
+    function_scope: usize
+}
+
+impl Resolver {
+    fn declare(&mut self, name: &str) -> Slot {
+        if self.scopes.len() == 1 {
+            return Slot::Global;
+        }
+
+        let scope = self.scopes.last_mut().unwrap();
+        let index = match scope.iter().position(|declared| declared == name) {
+            Some(index) => index,
+            None => {
+                scope.push(name.to_string());
+                scope.len() - 1
+            }
+        };
+
+        Slot::Local { depth: 0, index }
+    }
+
+    fn lookup(&self, name: &str) -> Option<Slot> {
+        for (scope_index, scope) in self.scopes.iter().enumerate().skip(self.function_scope).rev() {
+            if let Some(index) = scope.iter().position(|declared| declared == name) {
+                return Some(Slot::Local { depth: self.scopes.len() - 1 - scope_index, index });
+            }
+        }
+
+        match self.function_scope {
+            0 => Some(Slot::Global),
+            _ => None
+        }
+    }
+
+    fn scoped<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut Self)
+    {
+        self.scopes.push(vec![]);
+        f(self);
+        self.scopes.pop();
+    }
+
+    fn statement(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::VariableAssignment(var_assign) => {
+                self.expr(&mut var_assign.init_value);
+                var_assign.slot = Some(self.declare(&var_assign.name));
+            },
+            Statement::FunctionDeclaration(fn_decl) => {
+                let outer_function_scope = self.function_scope;
+                self.function_scope = self.scopes.len();
+
+                let (args, body) = (&fn_decl.args, &mut fn_decl.body);
+                self.scoped(|resolver| {
+                    for (arg_name, _) in args.iter() {
+                        resolver.declare(arg_name);
+                    }
+                    resolver.block(body);
+                });
+
+                self.function_scope = outer_function_scope;
+                self.declare(&fn_decl.name);
+            },
+            Statement::ModuleImport(mod_import) => {
+                for statement in mod_import.statements.iter_mut() {
+                    self.statement(statement);
+                }
+            },
+            Statement::UseDeclaration(_) => (),
+            Statement::Expr(expr) => self.expr(expr)
+        }
+    }
+
+    fn block(&mut self, block: &mut ExprBlock) {
+        for statement in block.body.iter_mut() {
+            self.statement(statement);
+        }
+    }
+
+    fn expr(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Literal(_) => (),
+            Expr::Binary(binary_expr) => {
+                self.expr(&mut binary_expr.lhs);
+                self.expr(&mut binary_expr.rhs);
+            },
+            Expr::Index(index_expr) => {
+                self.expr(&mut index_expr.index);
+                self.expr(&mut index_expr.value);
+            },
+            Expr::Unary(unary_expr) => self.expr(&mut unary_expr.value),
+            Expr::Field(field_expr) => {
+                if field_expr.child.is_none() {
+                    field_expr.slot = self.lookup(&field_expr.field_name);
+                }
+            },
+            Expr::Call(call_expr) => {
+                self.expr(&mut call_expr.path);
+                for arg_expr in call_expr.args.iter_mut() {
+                    self.expr(arg_expr);
+                }
+            },
+            Expr::Assign(assign_expr) => {
+                self.expr(&mut assign_expr.lhs);
+                self.expr(&mut assign_expr.rhs);
+            },
+            Expr::Block(block_expr) => self.block(block_expr),
+            Expr::For(for_expr) => {
+                self.expr(&mut for_expr.start_i);
+                self.expr(&mut for_expr.end_i);
+
+                let (index_var, body) = (&for_expr.index_var, &mut for_expr.body);
+                self.scoped(|resolver| {
+                    resolver.declare(index_var);
+                    resolver.block(body);
+                });
+            },
+            Expr::If(if_expr) => {
+                self.expr(&mut if_expr.condition_expr);
+
+                let body = &mut if_expr.body;
+                self.scoped(|resolver| resolver.block(body));
+            },
+            Expr::List(list_expr) => {
+                for item in list_expr.items.iter_mut() {
+                    self.expr(item);
+                }
+            },
+            Expr::Member(member_expr) => self.expr(&mut member_expr.value)
+        }
+    }
+}
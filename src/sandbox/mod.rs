@@ -0,0 +1,80 @@
+use std::fmt;
+
+/// Something a script can do outside of computing values and printing, which an embedder can
+/// deny to run untrusted code
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Capability {
+    /// Reading and writing files and directories
+    Filesystem,
+    /// Running other programs
+    Process,
+    /// Reading and changing environment variables and the working directory
+    Env,
+    /// Importing modules from disk with `mod`
+    ModuleImports
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Capability::Filesystem => write!(f, "filesystem"),
+            Capability::Process => write!(f, "process"),
+            Capability::Env => write!(f, "env"),
+            Capability::ModuleImports => write!(f, "module imports")
+        }
+    }
+}
+
+/// Which capabilities a script is allowed to use. Builtins needing a denied capability are not
+/// registered, using them is a parse error and calling them anyway is a runtime error.
+///
+/// The default allows everything but running programs and touching the environment, which the
+/// command line enables with `--allow-process`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Capabilities {
+    pub filesystem: bool,
+    pub process: bool,
+    pub env: bool,
+    pub module_imports: bool
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            filesystem: true,
+            process: false,
+            env: false,
+            module_imports: true
+        }
+    }
+}
+
+impl Capabilities {
+    pub fn all() -> Self {
+        Self {
+            filesystem: true,
+            process: true,
+            env: true,
+            module_imports: true
+        }
+    }
+
+    /// Nothing but pure computation, stdin and stdout
+    pub fn none() -> Self {
+        Self {
+            filesystem: false,
+            process: false,
+            env: false,
+            module_imports: false
+        }
+    }
+
+    pub fn allows(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::Filesystem => self.filesystem,
+            Capability::Process => self.process,
+            Capability::Env => self.env,
+            Capability::ModuleImports => self.module_imports
+        }
+    }
+}
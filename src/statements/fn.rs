@@ -1,6 +1,8 @@
+use std::rc::Rc;
+
 use crate::data::Data;
-use crate::errors::ParseErr;
-use crate::expressions::block::ExprBlock;
+use crate::errors::{ParseErr, RuntimeErr};
+use crate::expressions::{Evaluable, block::ExprBlock};
 use crate::helpers::destructive_loop;
 use crate::interpreter::Interpreter;
 use crate::tokenizer::{token::Token, token_type::TokenType};
@@ -13,7 +15,8 @@ pub struct FunctionDeclaration {
     pub name: String,
     pub args: Vec<(String, Type)>,
     pub return_type: Box<Type>,
-    pub body: ExprBlock
+    pub body: ExprBlock,
+    pub doc: Option<String>
 }
 
 impl FunctionDeclaration {
@@ -22,14 +25,16 @@ impl FunctionDeclaration {
             name,
             args,
             return_type,
-            body
+            body,
+            doc: None
         }
     }
 }
 
 impl Executable for FunctionDeclaration {
-    fn exec(&self, interpreter: &mut Interpreter) {
-        interpreter.memory.insert(self.name.to_string(), Data::Fn(self.clone()));
+    fn exec(&self, interpreter: &mut Interpreter) -> Result<(), RuntimeErr> {
+        interpreter.memory.insert(self.name.to_string(), Data::Fn(Rc::new(self.clone())));
+        Ok(())
     }
 }
 
@@ -53,12 +58,13 @@ impl ParseableStatement for FunctionDeclaration {
 
                         let first_token = parser.collector.next();
                         let body = ExprBlock::parse_block(parser, first_token)?;
+                        let return_type = body.type_check(parser);
                         parser.sim_memory.pop_scope();
 
-                        let fn_decl = FunctionDeclaration::new(name.to_string(), args, Box::new(Type::Bool), body);
-                        let fn_type = Type::Fn { args_types: fn_decl.args.clone(), return_type: fn_decl.return_type.clone() };
+                        let fn_type = Type::Fn { args_types: args.clone(), return_type: Box::new(return_type.clone()) };
+                        let name = parser.declare(name, fn_type);
 
-                        parser.sim_memory.insert(name.to_string(), fn_type);
+                        let fn_decl = FunctionDeclaration::new(name, args, Box::new(return_type), body);
                         Ok(Statement::FunctionDeclaration(fn_decl))
 
                     }
@@ -87,9 +93,9 @@ fn parse_args(parser: &mut Parser) -> Result<Vec<(String, Type)>, ParseErr> {
 
                         let next_token = parser.collector.next();
                         match &next_token.token {
-                            TokenType::Identifier(arg_type) if Type::from_name(arg_type).is_some() => {
+                            TokenType::Identifier(arg_type) if parser.type_from_name(arg_type).is_some() => {
 
-                                let arg_type = Type::from_name(arg_type).unwrap();
+                                let arg_type = parser.type_from_name(arg_type).unwrap();
                                 args.push((arg_name.to_string(), arg_type));
 
                                 let next_token = parser.collector.next();
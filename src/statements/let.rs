@@ -1,15 +1,20 @@
-use crate::errors::{map_err_token, ParseErr};
+use crate::data::Data;
+use crate::errors::{ParseErr, RuntimeErr};
 use crate::interpreter::Interpreter;
 use crate::tokenizer::{token::Token, token_type::TokenType};
 use crate::parser::Parser;
 use crate::expressions::Expr;
+use crate::memory::Slot;
 use crate::statements::{Executable, ParseableStatement, Statement};
 
 #[derive(Debug, Clone)]
 pub struct VariableAssignment {
     pub name: String,
     pub init_value: Expr,
-    pub is_mut: bool
+    pub is_mut: bool,
+    pub doc: Option<String>,
+    /// Filled in by the resolver, `None` if it has to be declared by name
+    pub slot: Option<Slot>
 }
 
 impl VariableAssignment {
@@ -17,15 +22,22 @@ impl VariableAssignment {
         VariableAssignment {
             name,
             init_value,
-            is_mut
+            is_mut,
+            doc: None,
+            slot: None
         }
     }
 }
 
 impl Executable for VariableAssignment {
-    fn exec(&self, interpreter: &mut Interpreter) {
-        let val = self.init_value.eval(interpreter);
-        interpreter.memory.insert(self.name.to_string(), val);
+    fn exec(&self, interpreter: &mut Interpreter) -> Result<(), RuntimeErr> {
+        let val = self.init_value.eval(interpreter)?;
+        // redeclaring a name in the same scope replaces its value
+        interpreter.store_memory(&val, |memory| {
+            memory.scopes.last().unwrap().get(&self.name).map_or(0, Data::heap_size)
+        })?;
+        interpreter.memory.insert_at(self.name.to_string(), val, self.slot);
+        Ok(())
     }
 }
 
@@ -50,9 +62,9 @@ impl ParseableStatement for VariableAssignment {
                         let expr = Expr::parse_expr(parser, expr_token)?;
                         let expr_type = expr.type_check(parser);
 
-                        parser.sim_memory.insert(name.to_string(), expr_type);
+                        let name = parser.declare(name, expr_type);
 
-                        Ok(Statement::VariableAssignment(VariableAssignment::new(name.to_string(), expr, is_mut)))
+                        Ok(Statement::VariableAssignment(VariableAssignment::new(name, expr, is_mut)))
 
                     },
                     _ => Err(parser.unexpected_token(next_token, "Equal"))
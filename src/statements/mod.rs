@@ -1,6 +1,6 @@
 use std::fmt::Debug;
 
-use crate::errors::ParseErr;
+use crate::errors::{ParseErr, ParseErrKind, RuntimeErr};
 use crate::expressions::Expr;
 use crate::interpreter::Interpreter;
 use crate::parser::Parser;
@@ -9,13 +9,15 @@ use crate::tokenizer::{token::Token, token_type::TokenType};
 use r#let::VariableAssignment;
 use r#fn::FunctionDeclaration;
 use module::ModuleImport;
+use r#use::UseDeclaration;
 
 pub mod r#let;
 pub mod r#fn;
 pub mod module;
+pub mod r#use;
 
 pub trait Executable {
-    fn exec(&self, interpreter: &mut Interpreter);
+    fn exec(&self, interpreter: &mut Interpreter) -> Result<(), RuntimeErr>;
 }
 
 pub trait ParseableStatement {
@@ -27,48 +29,110 @@ pub enum Statement {
     VariableAssignment(VariableAssignment),
     FunctionDeclaration(FunctionDeclaration),
     ModuleImport(ModuleImport),
+    UseDeclaration(UseDeclaration),
     Expr(Expr)
 }
 
 impl Statement {
-    pub fn exec(&self, interpreter: &mut Interpreter) {
+    pub fn exec(&self, interpreter: &mut Interpreter) -> Result<(), RuntimeErr> {
         match self {
             Statement::VariableAssignment(var_assign) => var_assign.exec(interpreter),
             Statement::FunctionDeclaration(func_decl) => func_decl.exec(interpreter),
             Statement::ModuleImport(mod_import) => mod_import.exec(interpreter),
+            Statement::UseDeclaration(use_decl) => use_decl.exec(interpreter),
             Statement::Expr(expr) => expr.exec(interpreter)
         }
     }
 
     pub fn parse_statement(parser: &mut Parser, first_token: &Token) -> Result<Statement, ParseErr> {
-        let res = match first_token.token {
+        if let TokenType::DocComment(_) = first_token.token {
+            return Statement::parse_documented(parser, first_token);
+        }
+
+        if let TokenType::Pub = first_token.token {
+            return Statement::parse_pub(parser);
+        }
+
+        let statement = match first_token.token {
             TokenType::Let => VariableAssignment::parse(parser, first_token),
             TokenType::Fn => FunctionDeclaration::parse(parser, first_token),
             TokenType::Mod => ModuleImport::parse(parser, first_token),
+            TokenType::Use => UseDeclaration::parse(parser, first_token),
             _ => Expr::parse(parser, first_token)
-        };
+        }?;
 
         let semicolon_token = parser.collector.next();
         match semicolon_token.token {
-            TokenType::Semicolon => res,
+            TokenType::Semicolon => Ok(statement),
             _ => {
-                let mut enforce_semicolon = true;
-
-                if let Ok(res) = &res {
-                    enforce_semicolon = !matches!(res,
-                        Statement::FunctionDeclaration(_) |
-                        Statement::Expr(Expr::For(_)) |
-                        Statement::Expr(Expr::If(_))
-                    );
-                }
+                let enforce_semicolon = !matches!(statement,
+                    Statement::FunctionDeclaration(_) |
+                    Statement::Expr(Expr::For(_)) |
+                    Statement::Expr(Expr::If(_))
+                );
 
                 if enforce_semicolon {
                     Err(parser.unexpected_token(semicolon_token, "Semicolon"))
                 } else {
                     parser.collector.back();
-                    res
+                    Ok(statement)
                 }
             }
         }
     }
+
+    /// Collects consecutive `///` comments and attaches them to the following `fn` or `let`
+    fn parse_documented(parser: &mut Parser, first_token: &Token) -> Result<Statement, ParseErr> {
+        let mut doc_lines = vec![];
+        let mut next_token = first_token;
+
+        while let TokenType::DocComment(doc_line) = &next_token.token {
+            doc_lines.push(doc_line.to_string());
+            next_token = parser.collector.next();
+        }
+
+        let documentable = match next_token.token {
+            TokenType::Fn | TokenType::Let => true,
+            // `pub mod` has nowhere to keep a doc comment
+            TokenType::Pub => {
+                let item_token = parser.collector.next();
+                parser.collector.back();
+                matches!(item_token.token, TokenType::Fn | TokenType::Let)
+            },
+            _ => false
+        };
+        if !documentable {
+            return Err(ParseErrKind::DanglingDocComment().from_token(first_token));
+        }
+
+        let doc = Some(doc_lines.join("\n"));
+        let mut statement = Statement::parse_statement(parser, next_token)?;
+
+        match &mut statement {
+            Statement::FunctionDeclaration(fn_decl) => fn_decl.doc = doc,
+            Statement::VariableAssignment(var_assign) => var_assign.doc = doc,
+            _ => unreachable!()
+        }
+
+        Ok(statement)
+    }
+
+    /// Parses a `pub` declaration, making the declared item accessible from outside its module
+    fn parse_pub(parser: &mut Parser) -> Result<Statement, ParseErr> {
+        let next_token = parser.collector.next();
+        let statement = match next_token.token {
+            TokenType::Fn | TokenType::Let | TokenType::Mod => Statement::parse_statement(parser, next_token)?,
+            _ => return Err(parser.unexpected_token(next_token, "Fn, Let or Mod"))
+        };
+
+        let name = match &statement {
+            Statement::FunctionDeclaration(fn_decl) => &fn_decl.name,
+            Statement::VariableAssignment(var_assign) => &var_assign.name,
+            Statement::ModuleImport(mod_import) => &mod_import.name,
+            _ => unreachable!()
+        };
+        parser.modules.set_private(name, false);
+
+        Ok(statement)
+    }
 }
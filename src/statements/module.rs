@@ -1,11 +1,20 @@
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::path::{Path, PathBuf};
 
-use crate::errors::ParseErr;
+use crate::errors::{ParseErr, ParseErrKind, RuntimeErr};
 use crate::interpreter::Interpreter;
+use crate::memory::Memory;
 use crate::tokenizer::tokenize;
 use crate::tokenizer::{token::Token, token_type::TokenType};
 use crate::parser::{Parser, TokenCollector};
+use crate::sandbox::Capability;
 use crate::statements::{Executable, ParseableStatement, Statement};
+use crate::types::Type;
+
+/// Source of the standard library, loaded as the `std` module unless overridden
+pub const STD_SOURCE: &str = include_str!("../../std.ox");
+pub const STD_MODULE: &str = "std";
 
 #[derive(Debug, Clone)]
 pub struct ModuleImport {
@@ -22,37 +31,265 @@ impl ModuleImport {
     }
 }
 
+/// Keeps track of the modules loaded while parsing a program so each module file is only
+/// parsed and run once, no matter how many times it's imported
+#[derive(Debug, Clone, Default)]
+pub struct ModuleRegistry {
+    /// Canonical file paths of loaded modules mapped to the module path their items live under
+    pub loaded: HashMap<PathBuf, String>,
+    /// Module paths of repeated imports mapped to the module path of the first import
+    pub aliases: HashMap<String, String>,
+    /// Mangled paths of items and modules declared without `pub`
+    pub private_items: HashSet<String>,
+    /// Canonical file paths of the modules currently being parsed, outermost first
+    pub loading: Vec<PathBuf>,
+    /// Canonical directory of each package in the project mapped to the module files of the
+    /// dependencies it declares, by name, see [`crate::manifest::Manifest::resolve_dependencies`]
+    pub dependencies: HashMap<PathBuf, HashMap<String, PathBuf>>,
+    /// Directories searched for modules that aren't next to the importing file or a dependency
+    pub search_paths: Vec<PathBuf>,
+    /// Items usable from every module without a path, mapped to their mangled paths
+    pub prelude: HashMap<String, String>,
+    /// Changes since the last [`ModuleRegistry::checkpoint`], if there is one, latest last
+    journal: Option<Vec<RegistryChange>>
+}
+
+/// A change to a registry that [`ModuleRegistry::rollback`] knows how to undo
+#[derive(Debug, Clone)]
+enum RegistryChange {
+    Loaded(PathBuf),
+    /// A module alias with the module path it replaced
+    Alias(String, Option<String>),
+    /// An item made private or public with whether it was private before
+    Private(String, bool)
+}
+
+impl ModuleRegistry {
+    /// Records that the module file at `file_path` has been parsed with its items under `module_path`
+    pub fn mark_loaded(&mut self, file_path: PathBuf, module_path: String) {
+        self.record(RegistryChange::Loaded(file_path.clone()));
+        self.loaded.insert(file_path, module_path);
+    }
+
+    pub fn set_alias(&mut self, module_path: String, loaded_module_path: String) {
+        let old = self.aliases.insert(module_path.to_string(), loaded_module_path);
+        self.record(RegistryChange::Alias(module_path, old));
+    }
+
+    pub fn set_private(&mut self, mangled: &str, private: bool) {
+        let was_private = if private {
+            !self.private_items.insert(mangled.to_string())
+        } else {
+            self.private_items.remove(mangled)
+        };
+        self.record(RegistryChange::Private(mangled.to_string(), was_private));
+    }
+
+    fn record(&mut self, change: RegistryChange) {
+        if let Some(journal) = &mut self.journal {
+            journal.push(change);
+        }
+    }
+
+    /// Starts keeping track of loaded modules, aliases and private items so
+    /// [`ModuleRegistry::rollback`] can undo them, like [`crate::memory::Memory::checkpoint`]
+    pub fn checkpoint(&mut self) {
+        self.journal = Some(vec![]);
+    }
+
+    /// Undoes every change since the checkpoint
+    pub fn rollback(&mut self) {
+        let Some(journal) = self.journal.take() else {
+            return;
+        };
+
+        for change in journal.into_iter().rev() {
+            match change {
+                RegistryChange::Loaded(file_path) => {
+                    self.loaded.remove(&file_path);
+                },
+                RegistryChange::Alias(module_path, Some(old)) => {
+                    self.aliases.insert(module_path, old);
+                },
+                RegistryChange::Alias(module_path, None) => {
+                    self.aliases.remove(&module_path);
+                },
+                RegistryChange::Private(mangled, true) => {
+                    self.private_items.insert(mangled);
+                },
+                RegistryChange::Private(mangled, false) => {
+                    self.private_items.remove(&mangled);
+                }
+            }
+        }
+    }
+
+    /// Keeps every change since the checkpoint
+    pub fn commit(&mut self) {
+        self.journal = None;
+    }
+
+    /// Replaces every module path within `mangled` that refers to an already loaded module
+    pub fn canonicalize_path(&self, mangled: &str) -> String {
+        let mut canonical = String::new();
+
+        for segment in mangled.split("::") {
+            if !canonical.is_empty() {
+                canonical.push_str("::");
+            }
+            canonical.push_str(segment);
+
+            if let Some(module_path) = self.aliases.get(&canonical) {
+                canonical = module_path.to_string();
+            }
+        }
+
+        canonical
+    }
+
+    /// Looks for `<name>.ox` or `<name>/mod.ox` next to the importing file, then for a dependency
+    /// called `name` of the package the importing file belongs to and finally in each of the
+    /// search paths
+    pub fn find_module_file(&self, importer_filename: &str, name: &str) -> Option<PathBuf> {
+        let importer_dir = Path::new(importer_filename).parent().unwrap_or(Path::new(""));
+
+        let find_in_dir = |dir: &Path| {
+            [dir.join(format!("{}.ox", name)), dir.join(name).join("mod.ox")]
+                .into_iter()
+                .find(|file_path| file_path.is_file())
+        };
+
+        find_in_dir(importer_dir)
+            .or_else(|| self.package_dependencies(importer_filename)?.get(name).cloned())
+            .or_else(|| self.search_paths.iter().find_map(|dir| find_in_dir(dir)))
+    }
+
+    /// Dependencies of the innermost package whose directory contains `filename`
+    fn package_dependencies(&self, filename: &str) -> Option<&HashMap<String, PathBuf>> {
+        let file_path = fs::canonicalize(filename).ok()?;
+
+        file_path.ancestors().find_map(|dir| self.dependencies.get(dir))
+    }
+
+    /// Returns the import chain if loading `file_path` would import a module into itself
+    fn find_cycle(&self, file_path: &PathBuf) -> Option<Vec<String>> {
+        let cycle_start = self.loading.iter().position(|loading| loading == file_path)?;
+
+        let chain = self.loading[cycle_start..].iter()
+            .chain([file_path])
+            .map(|file_path| file_path.display().to_string())
+            .collect();
+
+        Some(chain)
+    }
+}
+
+impl ModuleImport {
+    /// Parses `buf` as the `std` module and adds its public items to the prelude of `modules`
+    pub fn parse_std(filename: &str, buf: &str, sim_memory: &mut Memory<Type>, modules: &mut ModuleRegistry) -> Result<ModuleImport, ParseErr> {
+        let tokens = tokenize(filename, buf)?;
+        let collector = TokenCollector::new(&tokens);
+
+        let mut std_parser = Parser::new(collector, sim_memory);
+        std_parser.module_path = STD_MODULE.to_string();
+        std_parser.modules = std::mem::take(modules);
+
+        let statements = std_parser.generate_program();
+        *modules = std::mem::take(&mut std_parser.modules);
+
+        let std_prefix = format!("{}::", STD_MODULE);
+        for name in sim_memory.scopes[0].keys() {
+            if let Some(item_name) = name.strip_prefix(&std_prefix) {
+                if !item_name.contains("::") && !modules.private_items.contains(name) {
+                    modules.prelude.insert(item_name.to_string(), name.to_string());
+                }
+            }
+        }
+
+        Ok(ModuleImport::new(STD_MODULE.to_string(), statements))
+    }
+}
+
 impl Executable for ModuleImport {
-    fn exec(&self, interpreter: &mut Interpreter) {
-        let mut mod_interpreter = Interpreter::new(&self.statements, interpreter.memory);
-        mod_interpreter.run_program();
+    fn exec(&self, interpreter: &mut Interpreter) -> Result<(), RuntimeErr> {
+        for statement in self.statements.iter() {
+            statement.exec(interpreter)?;
+        }
+
+        Ok(())
     }
 }
 
 impl ParseableStatement for ModuleImport {
-    fn parse(parser: &mut Parser, _first_token: &Token) -> Result<Statement, ParseErr> {
+    fn parse(parser: &mut Parser, first_token: &Token) -> Result<Statement, ParseErr> {
         let next_token = parser.collector.next();
         match &next_token.token {
             TokenType::Identifier(name) => {
 
-                let mut filename = name.to_string();
-                filename.push_str(".ox");
+                if !parser.capabilities.allows(Capability::ModuleImports) {
+                    return Err(ParseErrKind::CapabilityDenied(format!("mod {}", name), Capability::ModuleImports).from_token(next_token));
+                }
+
+                let file_path = parser.modules.find_module_file(&first_token.token_pos.filename, name)
+                    .ok_or_else(|| ParseErrKind::ModuleNotFound(format!("{}.ox", name)).from_token(next_token))?;
+                let filename = file_path.to_string_lossy().to_string();
+
+                let module_path = parser.mangle(name);
+                parser.modules.set_private(&module_path, true);
+
+                if parser.modules.loading.is_empty() {
+                    if let Ok(importer_path) = fs::canonicalize(&first_token.token_pos.filename) {
+                        parser.modules.loading.push(importer_path);
+                    }
+                }
 
-                let buf = fs::read_to_string(filename)
-                    .expect("failed to read source file");
-                let buf = buf.trim();
+                let canonical_file_path = fs::canonicalize(&file_path)
+                    .map_err(|_| ParseErrKind::ModuleNotFound(filename.to_string()).from_token(next_token))?;
 
-                let tokens = tokenize(name, buf);
+                if let Some(chain) = parser.modules.find_cycle(&canonical_file_path) {
+                    return Err(ParseErrKind::ImportCycle(chain).from_token(next_token));
+                }
+
+                // the module has already been parsed and will already have run by the time this
+                // import runs, so only its path has to be made available
+                if let Some(loaded_module_path) = parser.modules.loaded.get(&canonical_file_path) {
+                    let loaded_module_path = loaded_module_path.to_string();
+                    parser.modules.set_alias(module_path.to_string(), loaded_module_path);
+
+                    return Ok(Statement::ModuleImport(ModuleImport::new(module_path, vec![])));
+                }
+
+                let buf = fs::read_to_string(&file_path)
+                    .map_err(|_| ParseErrKind::ModuleNotFound(filename.to_string()).from_token(next_token))?;
+
+                let tokens = tokenize(&filename, &buf)?;
                 let collector = TokenCollector::new(&tokens);
 
                 let mut buf_parser = Parser::new(collector, parser.sim_memory);
+                buf_parser.module_path = module_path.to_string();
+                buf_parser.capabilities = parser.capabilities;
+                buf_parser.host_types = parser.host_types.clone();
+                buf_parser.modules = std::mem::take(&mut parser.modules);
+                buf_parser.modules.loading.push(canonical_file_path.clone());
 
-                let statements = buf_parser.generate_program();
+                let res = buf_parser.parse_program();
 
-                // TODO do error handling stuff for this
-                Ok(Statement::ModuleImport(ModuleImport::new(name.to_string(), statements)))
+                parser.modules = std::mem::take(&mut buf_parser.modules);
+                parser.modules.loading.pop();
+                parser.modules.mark_loaded(canonical_file_path, module_path.to_string());
+
+                // all but the last error of the module are recorded directly so the import fails
+                // with a single error like any other statement
+                let statements = res.map_err(|mut errs| {
+                    let last_err = errs.pop().unwrap();
+                    parser.errors.extend(errs);
+                    last_err
+                })?;
+
+                Ok(Statement::ModuleImport(ModuleImport::new(module_path, statements)))
             },
-            _ => Err(parser.unexpected_token(next_token, "variable name"))
+            _ => Err(parser.unexpected_token(next_token, "module name"))
         }
     }
 }
+
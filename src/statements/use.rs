@@ -0,0 +1,54 @@
+use crate::errors::{ParseErr, ParseErrKind, RuntimeErr};
+use crate::expressions::path::parse_path_segments;
+use crate::interpreter::Interpreter;
+use crate::tokenizer::{token::Token, token_type::TokenType};
+use crate::parser::Parser;
+use crate::statements::{Executable, ParseableStatement, Statement};
+
+#[derive(Debug, Clone)]
+pub struct UseDeclaration {
+    pub path: String,
+    pub alias: String
+}
+
+impl UseDeclaration {
+    fn new(path: String, alias: String) -> UseDeclaration {
+        UseDeclaration {
+            path,
+            alias
+        }
+    }
+}
+
+impl Executable for UseDeclaration {
+    // paths are resolved while parsing so there's nothing left to do at runtime
+    fn exec(&self, _interpreter: &mut Interpreter) -> Result<(), RuntimeErr> {
+        Ok(())
+    }
+}
+
+impl ParseableStatement for UseDeclaration {
+    fn parse(parser: &mut Parser, _first_token: &Token) -> Result<Statement, ParseErr> {
+        let next_token = parser.collector.next();
+        match &next_token.token {
+            TokenType::Identifier(first_segment) => {
+
+                let path = parse_path_segments(parser, first_segment)?;
+                let mangled = parser.resolve_path(&path)
+                    .ok_or_else(|| ParseErrKind::UnknownField().from_token(next_token))?;
+
+                if !parser.is_visible(&path) {
+                    return Err(ParseErrKind::PrivateItem(path).from_token(next_token));
+                }
+
+                // alias the path as written so visibility is still checked against the modules
+                // it was imported through
+                let alias = path.rsplit("::").next().unwrap().to_string();
+                parser.aliases.insert(alias.to_string(), parser.mangle_written_path(&path));
+
+                Ok(Statement::UseDeclaration(UseDeclaration::new(mangled, alias)))
+            },
+            _ => Err(parser.unexpected_token(next_token, "path"))
+        }
+    }
+}
@@ -0,0 +1,993 @@
+use std::cell::RefCell;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use crate::{Command, Config, Engine, Error, Source, Value};
+use crate::builtin::BuiltinFn;
+use crate::data::{Data, ExprLiteral};
+use crate::dump::{dump_ast, dump_tokens, AstFormat};
+use crate::engine::{CustomType, Handle, TypeBuilder};
+use crate::errors::RuntimeErr;
+use crate::expressions::Expr;
+use crate::interpreter::{Interpreter, Limits};
+use crate::memory::Memory;
+use crate::optimizer::{optimize, OptLevel};
+use crate::parser::{Parser, TokenCollector};
+use crate::sandbox::{Capabilities, Capability};
+use crate::statements::Statement;
+use crate::statements::module::{ModuleImport, ModuleRegistry, STD_SOURCE};
+use crate::tokenizer::tokenize;
+use crate::types::Type;
+
+fn parse_file(filename: &str, buf: &str) -> Vec<Statement> {
+    let mut sim_memory = Memory::new();
+    BuiltinFn::populate_sim_memory(&mut sim_memory, &Capabilities::default());
+
+    let tokens = tokenize(filename, buf).unwrap();
+    let collector = TokenCollector::new(&tokens);
+
+    let mut parser = Parser::new(collector, &mut sim_memory);
+    parser.generate_program()
+}
+
+fn parse(buf: &str) -> Vec<Statement> {
+    parse_file("test.ox", buf)
+}
+
+/// Runs the entry file of a project written to a fresh temporary directory, returning the
+/// global memory afterwards
+fn run_project(test_name: &str, files: &[(&str, &str)]) -> Memory<Data> {
+    let dir = std::env::temp_dir().join(format!("oxid-test-{}-{}", test_name, std::process::id()));
+    for (filename, buf) in files {
+        let file_path: PathBuf = dir.join(filename);
+        fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        fs::write(file_path, buf).unwrap();
+    }
+
+    let (entry_filename, entry_buf) = files[0];
+    let statements = parse_file(&dir.join(entry_filename).to_string_lossy(), entry_buf);
+
+    let mut memory = Memory::new();
+    BuiltinFn::populate_memory(&mut memory, &Capabilities::default());
+    Interpreter::new(&statements, &mut memory, Capabilities::default()).run_program().unwrap();
+
+    fs::remove_dir_all(dir).unwrap();
+    memory
+}
+
+/// Collects everything written to it, shared so it can still be read after the interpreter that
+/// wrote to it is dropped
+#[derive(Clone, Default)]
+struct SharedBuf(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuf {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SharedBuf {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.borrow().clone()).unwrap()
+    }
+}
+
+fn get_number(memory: &Memory<Data>, name: &str) -> f64 {
+    match memory.get(name) {
+        Some(Data::Number(number)) => *number,
+        other => panic!("expected `{}` to be a number, got {:?}", name, other)
+    }
+}
+
+#[test]
+fn doc_comments_are_attached() {
+    let statements = parse("
+        /// the answer
+        /// to everything
+        let x = 42;
+
+        // not documentation
+        fn f() { x; }
+    ");
+
+    match &statements[..] {
+        [Statement::VariableAssignment(var_assign), Statement::FunctionDeclaration(fn_decl)] => {
+            assert_eq!(var_assign.doc.as_deref(), Some("the answer\nto everything"));
+            assert_eq!(fn_decl.doc, None);
+        },
+        _ => panic!("unexpected statements {:?}", statements)
+    }
+}
+
+#[test]
+#[should_panic(expected = "doc comments must be followed by a `fn` or `let`")]
+fn dangling_doc_comment() {
+    parse("/// nothing to document\nprint(\"hi\");");
+}
+
+#[test]
+#[should_panic(expected = "doc comments must be followed by a `fn` or `let`")]
+fn doc_comment_on_pub_mod() {
+    parse("/// not kept anywhere\npub mod foo;");
+}
+
+#[test]
+fn modules_are_namespaced() {
+    let memory = run_project("namespaced", &[
+        ("main.ox", "mod foo; let x = 1; let y = foo::get();"),
+        ("foo.ox", "let x = 2; pub fn get() { x; }")
+    ]);
+
+    assert_eq!(get_number(&memory, "x"), 1.0);
+    assert_eq!(get_number(&memory, "y"), 2.0);
+    assert_eq!(get_number(&memory, "foo::x"), 2.0);
+}
+
+#[test]
+fn use_imports_items() {
+    let memory = run_project("use", &[
+        ("main.ox", "mod util; use util::nested::double; let y = double(4);"),
+        ("util.ox", "pub mod nested;"),
+        ("nested.ox", "pub fn double(x: Number) { x * 2; }")
+    ]);
+
+    assert_eq!(get_number(&memory, "y"), 8.0);
+}
+
+#[test]
+fn modules_resolve_relative_to_importer() {
+    let memory = run_project("relative", &[
+        ("app/main.ox", "mod helpers; let y = helpers::VALUE;"),
+        ("app/helpers/mod.ox", "pub let VALUE = 3;")
+    ]);
+
+    assert_eq!(get_number(&memory, "y"), 3.0);
+}
+
+#[test]
+#[should_panic(expected = "`foo::secret` is private")]
+fn private_items_are_hidden() {
+    run_project("private", &[
+        ("main.ox", "mod foo; print(foo::secret);"),
+        ("foo.ox", "let secret = \"shh\";")
+    ]);
+}
+
+#[test]
+fn modules_are_loaded_once() {
+    let memory = run_project("loaded-once", &[
+        ("main.ox", "mod counter; mod user; counter::bump(); let y = counter::COUNT; let z = user::counter::COUNT;"),
+        ("user.ox", "pub mod counter; counter::bump();"),
+        ("counter.ox", "pub let mut COUNT = 0; pub fn bump() { COUNT += 1; }")
+    ]);
+
+    // the module body only ran once so both bumps changed the same variable
+    assert_eq!(get_number(&memory, "y"), 2.0);
+    assert_eq!(get_number(&memory, "z"), 2.0);
+}
+
+#[test]
+#[should_panic(expected = "circular module import")]
+fn circular_imports_are_detected() {
+    run_project("circular", &[
+        ("main.ox", "mod a;"),
+        ("a.ox", "mod b;"),
+        ("b.ox", "mod a;")
+    ]);
+}
+
+#[test]
+fn std_is_a_prelude() {
+    let mut sim_memory = Memory::new();
+    BuiltinFn::populate_sim_memory(&mut sim_memory, &Capabilities::default());
+
+    let mut modules = ModuleRegistry::default();
+    let std_import = ModuleImport::parse_std("<std>", STD_SOURCE, &mut sim_memory, &mut modules).unwrap();
+
+    let tokens = tokenize("test.ox", "let a = to_string(1); let b = std::to_string(2);").unwrap();
+    let mut parser = Parser::new(TokenCollector::new(&tokens), &mut sim_memory);
+    parser.modules = modules;
+
+    let mut statements = vec![Statement::ModuleImport(std_import)];
+    statements.extend(parser.generate_program());
+
+    let mut memory = Memory::new();
+    BuiltinFn::populate_memory(&mut memory, &Capabilities::default());
+    Interpreter::new(&statements, &mut memory, Capabilities::default()).run_program().unwrap();
+
+    assert!(matches!(memory.get(&String::from("a")), Some(Data::String(a)) if a.as_str() == "1"));
+    assert!(matches!(memory.get(&String::from("b")), Some(Data::String(b)) if b.as_str() == "2"));
+}
+
+fn config(args: &[&str]) -> Result<Config, String> {
+    let mut args = std::iter::once("oxid").chain(args.iter().copied()).map(String::from);
+    Config::new(&mut args)
+}
+
+#[test]
+fn config_parses_subcommands_and_script_args() {
+    let config = config(&["check", "-e", "print(1);", "a", "b"]).unwrap();
+
+    assert_eq!(config.command, Command::Check);
+    assert_eq!(config.source, Some(Source::Inline(String::from("print(1);"))));
+    assert_eq!(config.script_args, vec!["a", "b"]);
+
+    let config = self::config(&["--no-std", "script.ox", "--", "--verbose"]).unwrap();
+
+    assert_eq!(config.command, Command::Run);
+    assert_eq!(config.source, Some(Source::File(String::from("script.ox"))));
+    assert_eq!(config.script_args, vec!["--verbose"]);
+    assert!(config.no_std);
+}
+
+#[test]
+fn config_rejects_unknown_options() {
+    assert!(config(&["--frobnicate"]).is_err());
+    assert!(config(&["--std"]).is_err());
+    assert_eq!(config(&["--help"]).unwrap().command, Command::Help);
+}
+
+#[test]
+fn args_and_lists() {
+    let statements = parse("let a = args(); let n = len(a); let first = a[0]; let c = \"héllo\"[1];");
+
+    let mut memory = Memory::new();
+    BuiltinFn::populate_memory(&mut memory, &Capabilities::default());
+    let mut interpreter = Interpreter::new(&statements, &mut memory, Capabilities::default());
+    interpreter.script_args = vec![String::from("x"), String::from("y")];
+    interpreter.run_program().unwrap();
+
+    assert_eq!(get_number(&memory, "n"), 2.0);
+    assert!(matches!(memory.get(&String::from("first")), Some(Data::String(first)) if first.as_str() == "x"));
+    assert!(matches!(memory.get(&String::from("c")), Some(Data::String(c)) if c.as_str() == "é"));
+}
+
+#[test]
+fn all_errors_are_collected() {
+    let mut sim_memory = Memory::new();
+    BuiltinFn::populate_sim_memory(&mut sim_memory, &Capabilities::default());
+
+    let tokens = tokenize("test.ox", "
+        let a = 1 + true;
+        fn f(x: Number) {
+            let y = x + \"s\";
+        }
+        let ok = 1;
+        let b = ok + missing;
+    ").unwrap();
+    let mut parser = Parser::new(TokenCollector::new(&tokens), &mut sim_memory);
+
+    let errs = parser.parse_program().unwrap_err();
+    let lines: Vec<_> = errs.iter().map(|err| err.token_pos.line_i + 1).collect();
+
+    assert_eq!(lines, vec![2, 4, 7]);
+    assert!(sim_memory.has(&String::from("ok")));
+}
+
+#[test]
+fn invalid_arguments_to_a_callee_without_a_name() {
+    let mut sim_memory = Memory::new();
+    BuiltinFn::populate_sim_memory(&mut sim_memory, &Capabilities::default());
+
+    let tokens = tokenize("test.ox", "fn f(a: Number) { a; } [f][0](\"x\");").unwrap();
+    let mut parser = Parser::new(TokenCollector::new(&tokens), &mut sim_memory);
+
+    let errs: Vec<_> = parser.parse_program().unwrap_err().iter().map(|err| err.to_string()).collect();
+    assert_eq!(errs, vec!["test.ox:1:30: invalid arguments (String) passed to `<expression>`"]);
+}
+
+#[test]
+fn format_argument_count_is_checked() {
+    let mut sim_memory = Memory::new();
+    BuiltinFn::populate_sim_memory(&mut sim_memory, &Capabilities::default());
+
+    let tokens = tokenize("test.ox", "format(\"{} {}\", 1); format(\"{{}}\", 2);").unwrap();
+    let mut parser = Parser::new(TokenCollector::new(&tokens), &mut sim_memory);
+
+    let errs: Vec<_> = parser.parse_program().unwrap_err().iter().map(|err| err.to_string()).collect();
+    assert_eq!(errs, vec![
+        "test.ox:1:7: `format` template has 2 placeholders but got 1 argument",
+        "test.ox:1:27: `format` template has 0 placeholders but got 1 argument"
+    ]);
+
+    let (_, res) = run_on_both_backends("let t = \"{}\"; format(t, 1, 2);");
+    assert_eq!(res, Err(RuntimeErr::FormatArgCount(1, 2)));
+}
+
+#[test]
+fn rolling_back_forgets_failed_global_declarations() {
+    // what the repl does with an input that fails to parse
+    let mut sim_memory = Memory::new();
+    BuiltinFn::populate_sim_memory(&mut sim_memory, &Capabilities::default());
+    sim_memory.insert(String::from("x"), Type::Number);
+
+    sim_memory.checkpoint();
+    let tokens = tokenize("test.ox", "let w = \"s\"; let x = \"s\"; let bad = 1 + true;").unwrap();
+    assert!(Parser::new(TokenCollector::new(&tokens), &mut sim_memory).parse_program().is_err());
+    sim_memory.rollback();
+
+    assert!(!sim_memory.has("w"));
+    assert_eq!(sim_memory.get("x"), Some(&Type::Number));
+
+    // and with one that fails at runtime
+    let statements = parse("let w = \"s\"; let x = \"s\"; let bad = [1][3];");
+    let mut memory = Memory::new();
+    BuiltinFn::populate_memory(&mut memory, &Capabilities::default());
+    memory.insert(String::from("x"), Data::Number(1.0));
+
+    memory.checkpoint();
+    assert!(Interpreter::new(&statements, &mut memory, Capabilities::default()).run_program().is_err());
+    memory.rollback();
+
+    assert!(!memory.has("w"));
+    assert_eq!(get_number(&memory, "x"), 1.0);
+}
+
+#[test]
+fn exit_unwinds_the_interpreter() {
+    let statements = parse("let a = 1; fn f() { exit(3); a = 2; } f(); a = 3;");
+
+    let mut memory = Memory::new();
+    BuiltinFn::populate_memory(&mut memory, &Capabilities::default());
+    let res = Interpreter::new(&statements, &mut memory, Capabilities::default()).run_program();
+
+    assert_eq!(res, Err(RuntimeErr::Exit(3)));
+    assert_eq!(get_number(&memory, "a"), 1.0);
+    assert_eq!(memory.scopes.len(), 1);
+}
+
+#[test]
+fn index_out_of_bounds_is_a_runtime_error() {
+    let statements = parse("let a = args()[2];");
+
+    let mut memory = Memory::new();
+    BuiltinFn::populate_memory(&mut memory, &Capabilities::default());
+    let res = Interpreter::new(&statements, &mut memory, Capabilities::default()).run_program();
+
+    assert_eq!(res, Err(RuntimeErr::IndexOutOfBounds(2, 0)));
+}
+
+#[test]
+fn print_writes_to_the_interpreter_stdout() {
+    let statements = parse("print(\"a\"); print(1); println(\"b\"); println(parse_number(\"2\"));");
+    let out = SharedBuf::default();
+
+    let mut memory = Memory::new();
+    BuiltinFn::populate_memory(&mut memory, &Capabilities::default());
+    let mut interpreter = Interpreter::new(&statements, &mut memory, Capabilities::default());
+    interpreter.stdout = Box::new(out.clone());
+    interpreter.run_program().unwrap();
+
+    assert_eq!(out.contents(), "a1b\nSome(2)\n");
+}
+
+#[test]
+fn list_literals_and_records() {
+    // records only come from builtins, so one is put in memory the way `run` returns it
+    let record = Data::Record(Rc::new(vec![
+        (String::from("status"), Data::Number(3.0)),
+        (String::from("stdout"), Data::String(Rc::new(String::from("hi\n"))))
+    ]));
+
+    let mut sim_memory = Memory::new();
+    BuiltinFn::populate_sim_memory(&mut sim_memory, &Capabilities::default());
+    sim_memory.insert(String::from("out"), record.get_type());
+
+    let tokens = tokenize("test.ox", "
+        let status = out.status;
+        let lines = [out.stdout, \"bye\"];
+        let last = lines[1];
+        let count = len(lines);
+    ").unwrap();
+    let statements = Parser::new(TokenCollector::new(&tokens), &mut sim_memory).generate_program();
+
+    let mut memory = Memory::new();
+    BuiltinFn::populate_memory(&mut memory, &Capabilities::default());
+    memory.insert(String::from("out"), record);
+    Interpreter::new(&statements, &mut memory, Capabilities::default()).run_program().unwrap();
+
+    assert_eq!(get_number(&memory, "status"), 3.0);
+    assert_eq!(get_number(&memory, "count"), 2.0);
+    assert!(matches!(memory.get("last"), Some(Data::String(last)) if last.as_str() == "bye"));
+    assert!(matches!(memory.get("lines"), Some(Data::List(lines)) if lines[0].to_string() == "hi\n"));
+}
+
+#[test]
+#[cfg(unix)]
+fn run_returns_the_status_and_output_of_a_process() {
+    let mut sim_memory = Memory::new();
+    BuiltinFn::populate_sim_memory(&mut sim_memory, &Capabilities::all());
+
+    let tokens = tokenize("test.ox", "
+        let out = unwrap(run(\"sh\", [\"-c\", \"echo hi; exit 3\"]));
+        let status = out.status;
+        let stdout = out.stdout;
+    ").unwrap();
+    let mut parser = Parser::new(TokenCollector::new(&tokens), &mut sim_memory);
+    parser.capabilities = Capabilities::all();
+    let statements = parser.generate_program();
+
+    let mut memory = Memory::new();
+    BuiltinFn::populate_memory(&mut memory, &Capabilities::all());
+    Interpreter::new(&statements, &mut memory, Capabilities::all()).run_program().unwrap();
+
+    assert_eq!(get_number(&memory, "status"), 3.0);
+    assert!(matches!(memory.get(&String::from("stdout")), Some(Data::String(stdout)) if stdout.as_str() == "hi\n"));
+}
+
+#[test]
+#[should_panic(expected = "`run` needs the process capability")]
+fn process_builtins_are_gated() {
+    parse("let out = run(\"ls\", []);");
+}
+
+#[test]
+fn sandboxed_programs_cannot_import_or_touch_files() {
+    let mut sim_memory = Memory::new();
+    BuiltinFn::populate_sim_memory(&mut sim_memory, &Capabilities::none());
+
+    let tokens = tokenize("test.ox", "mod foo; let a = read_file(\"x\"); let b = len(\"ok\");").unwrap();
+    let mut parser = Parser::new(TokenCollector::new(&tokens), &mut sim_memory);
+    parser.capabilities = Capabilities::none();
+
+    let errs: Vec<_> = parser.parse_program().unwrap_err().iter().map(|err| err.to_string()).collect();
+    assert_eq!(errs, vec![
+        "test.ox:1:5: `mod foo` needs the module imports capability",
+        "test.ox:1:18: `read_file` needs the filesystem capability"
+    ]);
+}
+
+#[test]
+fn denied_builtins_fail_at_runtime() {
+    let statements = parse("let a = exists(\"/\");");
+
+    let mut memory = Memory::new();
+    BuiltinFn::populate_memory(&mut memory, &Capabilities::default());
+    let res = Interpreter::new(&statements, &mut memory, Capabilities::none()).run_program();
+
+    assert_eq!(res, Err(RuntimeErr::CapabilityDenied(String::from("exists"), Capability::Filesystem)));
+}
+
+fn run_limited(buf: &str, limits: Limits) -> (Result<(), RuntimeErr>, Memory<Data>) {
+    let statements = parse(buf);
+
+    let mut memory = Memory::new();
+    BuiltinFn::populate_memory(&mut memory, &Capabilities::default());
+    let mut interpreter = Interpreter::new(&statements, &mut memory, Capabilities::default());
+    interpreter.limits = limits;
+    let res = interpreter.run_program();
+
+    (res, memory)
+}
+
+#[test]
+fn unbounded_recursion_hits_the_depth_limit() {
+    // the second `f` calls whichever `f` is in memory when it runs, which is itself
+    let (res, memory) = run_limited("let mut d = 0; fn f() {} fn f() { d += 1; f(); } f();", Limits::default());
+
+    assert_eq!(res, Err(RuntimeErr::CallDepthExceeded(Limits::DEFAULT_MAX_DEPTH)));
+    assert_eq!(get_number(&memory, "d"), Limits::DEFAULT_MAX_DEPTH as f64);
+    assert_eq!(memory.scopes.len(), 1);
+}
+
+#[test]
+fn calls_between_backends_count_against_the_depth_limit() {
+    // `t` is run by the tree walker and the second `v` by the VM, each calling the other
+    let mut tree = parse("let mut d = 0; fn v() {} fn t() { v(); } fn v() { d += 1; t(); } v();");
+    let vm = tree.split_off(3);
+
+    let mut memory = Memory::new();
+    BuiltinFn::populate_memory(&mut memory, &Capabilities::default());
+    let mut interpreter = Interpreter::new(&tree, &mut memory, Capabilities::default());
+    interpreter.limits = Limits { max_depth: Some(10), ..Limits::default() };
+    interpreter.run_program().unwrap();
+    let res = crate::vm::run(&mut interpreter, &crate::vm::compile(&vm));
+    drop(interpreter);
+
+    assert_eq!(res, Err(RuntimeErr::CallDepthExceeded(10)));
+    assert_eq!(get_number(&memory, "d"), 5.0);
+    assert_eq!(memory.scopes.len(), 1);
+}
+
+#[test]
+fn step_and_memory_limits() {
+    let limits = Limits { max_steps: Some(1000), ..Limits::default() };
+    let (res, _) = run_limited("let mut n = 0; for i in 0..1000000 { n += 1; }", limits);
+    assert_eq!(res, Err(RuntimeErr::StepLimitExceeded(1000)));
+
+    let limits = Limits { max_memory: Some(1024), ..Limits::default() };
+    let (res, memory) = run_limited("let mut s = \"x\"; for i in 0..20 { s += s; }", limits);
+    assert_eq!(res, Err(RuntimeErr::MemoryLimitExceeded(1024)));
+    assert!(matches!(memory.get(&String::from("s")), Some(Data::String(s)) if s.len() == 1024));
+
+    // the value being replaced no longer counts, however the variable is reassigned
+    for program in ["let mut s = \"x\"; for i in 0..20 { s = s + s; }", "let s = \"x\"; let s = s + s; let s = s + s; let s = s + s; \
+        let s = s + s; let s = s + s; let s = s + s; let s = s + s; let s = s + s; let s = s + s; let s = s + s; let s = s + s;"] {
+        let (res, memory) = run_limited(program, limits);
+        assert_eq!(res, Err(RuntimeErr::MemoryLimitExceeded(1024)));
+        assert!(matches!(memory.get(&String::from("s")), Some(Data::String(s)) if s.len() == 1024));
+    }
+
+    let (res, _) = run_limited("let mut s = \"x\"; for i in 0..20 { s += s; }", Limits::unlimited());
+    assert_eq!(res, Ok(()));
+}
+
+#[test]
+fn config_parses_limits() {
+    let config = config(&["--max-steps", "10", "--max-memory", "64", "-e", "1;"]).unwrap();
+
+    assert_eq!(config.limits, Limits { max_steps: Some(10), max_depth: Some(Limits::DEFAULT_MAX_DEPTH), max_memory: Some(64) });
+    assert!(self::config(&["--max-depth", "deep"]).is_err());
+}
+
+#[test]
+fn engine_keeps_globals_between_evals() {
+    let mut engine = Engine::new();
+
+    assert_eq!(engine.eval("1 + 2").unwrap(), Value::Number(3.0));
+    assert_eq!(engine.eval("let a = [1, 2];").unwrap(), Value::Nil);
+    assert_eq!(engine.eval("len(a)").unwrap(), Value::from(2));
+
+    engine.set_global("name", "oxid").unwrap();
+    engine.set_global("flags", vec![true, false]).unwrap();
+    assert_eq!(engine.eval("format(\"{} {}\", name, flags[1])").unwrap(), Value::from("oxid false"));
+
+    let a: Vec<f64> = engine.get_global("a").unwrap().try_into().unwrap();
+    assert_eq!(a, vec![1.0, 2.0]);
+    assert_eq!(engine.get_global("missing"), None);
+}
+
+#[test]
+fn engine_errors_leave_globals_untouched() {
+    let mut engine = Engine::new();
+    engine.eval("let a = 1;").unwrap();
+
+    assert!(matches!(engine.eval("let a = \"s\"; let b = 1 + true;"), Err(Error::Parse(errs)) if errs.len() == 1));
+    assert!(matches!(engine.eval("let a = \"s\"; exit(2);"), Err(Error::Runtime(RuntimeErr::Exit(2)))));
+    assert_eq!(engine.get_global("a"), Some(Value::Number(1.0)));
+    assert_eq!(engine.eval("a + 1").unwrap(), Value::Number(2.0));
+
+    let res: Result<String, _> = engine.get_global("a").unwrap().try_into();
+    assert_eq!(res.unwrap_err().to_string(), "expected String, got Number");
+}
+
+#[test]
+fn engine_globals_keep_their_type() {
+    let mut engine = Engine::new();
+    engine.eval("let a = 1; fn f() { a + 1; }").unwrap();
+
+    let err = engine.set_global("a", "str").unwrap_err();
+    assert_eq!(err.to_string(), "expected Number, got String");
+    assert_eq!(engine.eval("f()").unwrap(), Value::Number(2.0));
+
+    engine.set_global("a", 41).unwrap();
+    assert_eq!(engine.eval("f()").unwrap(), Value::Number(42.0));
+}
+
+#[test]
+fn engine_functions_only_equal_themselves() {
+    let mut engine = Engine::new();
+    engine.eval("fn f(a: Number) { a; } let g = f;").unwrap();
+    let f = engine.get_global("f").unwrap();
+
+    assert_eq!(engine.get_global("g").unwrap(), f);
+    assert_eq!(engine.eval("len").unwrap(), engine.eval("len").unwrap());
+
+    engine.eval("fn f(a: String) { a; }").unwrap();
+    assert_ne!(engine.get_global("f").unwrap(), f);
+}
+
+#[test]
+fn engine_runs_files_with_their_modules() {
+    let dir = std::env::temp_dir().join("oxid_engine_run_file");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("main.ox"), "mod util; let n = util::double(4);").unwrap();
+    fs::write(dir.join("util.ox"), "pub fn double(x: Number) { x + x; }").unwrap();
+
+    let mut engine = Engine::new();
+    engine.run_file(dir.join("main.ox")).unwrap();
+
+    assert_eq!(engine.get_global("n"), Some(Value::Number(8.0)));
+    assert!(matches!(engine.run_file(dir.join("missing.ox")), Err(Error::Io(_))));
+}
+
+#[test]
+fn engine_errors_forget_failed_imports() {
+    let dir = std::env::temp_dir().join("oxid_engine_failed_import");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("bad.ox"), "mod util; use util::one; let b = 1 + true;").unwrap();
+    fs::write(dir.join("good.ox"), "mod util; let n = util::one();").unwrap();
+    fs::write(dir.join("util.ox"), "pub fn one() { 1; }").unwrap();
+
+    let mut engine = Engine::new();
+    assert!(matches!(engine.run_file(dir.join("bad.ox")), Err(Error::Parse(_))));
+    assert!(matches!(engine.eval("one();"), Err(Error::Parse(_))));
+
+    engine.run_file(dir.join("good.ox")).unwrap();
+    assert_eq!(engine.get_global("n"), Some(Value::Number(1.0)));
+}
+
+#[test]
+fn host_functions_are_type_checked_and_called() {
+    let mut engine = Engine::new();
+    engine.register_fn("add", |a: f64, b: f64| a + b);
+    engine.register_fn("shout", |s: String| s.to_uppercase());
+    engine.register_fn("first", |items: Vec<String>| items.first().cloned());
+    engine.register_fn("check", |n: f64| if n > 0.0 { Ok(n) } else { Err(String::from("not positive")) });
+    engine.register_fn("answer", || 42.0);
+
+    assert_eq!(engine.eval("add(1, answer())").unwrap(), Value::Number(43.0));
+    assert_eq!(engine.eval("shout(\"hi\")").unwrap(), Value::from("HI"));
+    assert_eq!(engine.eval("unwrap_or(first([\"a\", \"b\"]), \"none\")").unwrap(), Value::from("a"));
+    assert_eq!(engine.eval("unwrap(err_msg(check(0)))").unwrap(), Value::from("not positive"));
+
+    let errs = match engine.eval("add(\"1\", 2)") {
+        Err(Error::Parse(errs)) => errs,
+        res => panic!("expected a parse error, got {:?}", res)
+    };
+    assert_eq!(errs[0].to_string(), "<eval>:1:4: invalid arguments (String, Number) passed to `add`");
+}
+
+struct Request {
+    path: String,
+    headers: Vec<(String, String)>
+}
+
+impl CustomType for Request {
+    const NAME: &'static str = "Request";
+
+    fn build(builder: &mut TypeBuilder<Self>) {
+        builder
+            .getter("path", |req: &Request| req.path.to_string())
+            .setter("path", |req: &mut Request, path: String| req.path = path)
+            .getter("header_count", |req: &Request| req.headers.len() as f64)
+            .method("header", |req: &mut Request, name: String| {
+                req.headers.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string())
+            })
+            .method("with_path", |req: &mut Request, path: String| {
+                Handle::new(Request { path, headers: req.headers.clone() })
+            });
+    }
+}
+
+#[test]
+fn host_types_have_fields_and_methods() {
+    let req = Handle::new(Request {
+        path: String::from("/"),
+        headers: vec![(String::from("host"), String::from("example.com"))]
+    });
+
+    let mut engine = Engine::new();
+    engine.register_type::<Request>();
+    engine.set_global("req", req.clone()).unwrap();
+
+    assert_eq!(engine.eval("unwrap(req.header(\"host\"))").unwrap(), Value::from("example.com"));
+    assert_eq!(engine.eval("req.with_path(\"/b\").path").unwrap(), Value::from("/b"));
+
+    engine.eval("fn nest(r: Request, dir: String) { r.path += dir; } nest(req, \"a/\");").unwrap();
+    assert_eq!(req.borrow().path, "/a/");
+
+    let handle: Handle<Request> = engine.get_global("req").unwrap().try_into().unwrap();
+    assert_eq!(handle.borrow().path, "/a/");
+}
+
+#[test]
+fn host_types_are_type_checked() {
+    let mut engine = Engine::new();
+    engine.set_global("req", Handle::new(Request { path: String::from("/"), headers: vec![] })).unwrap();
+
+    let errs = |res: Result<Value, Error>| match res {
+        Err(Error::Parse(errs)) => errs.iter().map(|err| err.to_string()).collect::<Vec<_>>(),
+        res => panic!("expected a parse error, got {:?}", res)
+    };
+
+    assert_eq!(errs(engine.eval("req.header_count = 2")), vec!["<eval>:1:1: field `header_count` of Request can't be assigned to"]);
+    assert_eq!(errs(engine.eval("req.path = 2")), vec!["<eval>:1:1: cannot assign Number to a field of type String"]);
+    assert_eq!(errs(engine.eval("req.method")), vec!["<eval>:1:5: Request has no field `method`"]);
+    assert_eq!(errs(engine.eval("req.header(1)")), vec!["<eval>:1:11: invalid arguments (Number) passed to `header`"]);
+}
+
+/// Runs a program on both backends, checking they print the same and end with the same result
+fn run_on_both_backends(buf: &str) -> (String, Result<(), RuntimeErr>) {
+    run_statements_on_both_backends(&parse(buf))
+}
+
+fn run_statements_on_both_backends(statements: &Vec<Statement>) -> (String, Result<(), RuntimeErr>) {
+    let run = |use_vm: bool| {
+        let out = SharedBuf::default();
+
+        let mut memory = Memory::new();
+        BuiltinFn::populate_memory(&mut memory, &Capabilities::default());
+
+        let mut interpreter = Interpreter::new(statements, &mut memory, Capabilities::default());
+        interpreter.stdout = Box::new(out.clone());
+
+        let res = match use_vm {
+            true => crate::vm::run(&mut interpreter, &crate::vm::compile(statements)),
+            false => interpreter.run_program()
+        };
+        drop(interpreter);
+
+        assert_eq!(memory.scopes.len(), 1);
+        (out.contents(), res)
+    };
+
+    let tree = run(false);
+    assert_eq!(tree, run(true));
+    tree
+}
+
+#[test]
+fn vm_matches_the_tree_walker() {
+    let (out, res) = run_on_both_backends("
+        fn fizzbuzz(n: Number) {
+            for i in 1..n {
+                let mut out = \"\";
+                if i % 3 == 0 { out += \"Fizz\"; };
+                if i % 5 == 0 { out += \"Buzz\"; };
+                if out == \"\" { out = str(i); };
+                println(out);
+            };
+        }
+        fizzbuzz(16);
+
+        fn double(x: Number) { x + x; }
+        let word = \"héllo\";
+        println(format(\"{} {} {} {}\", word[1], len(word), { let x = 2; x + 1; }, double(21)));
+    ");
+
+    assert!(out.starts_with("1\n2\nFizz\n4\nBuzz\n"));
+    assert!(out.ends_with("14\nFizzBuzz\né 5 3 42\n"));
+    assert_eq!(res, Ok(()));
+}
+
+#[test]
+fn vm_fails_like_the_tree_walker() {
+    let (out, res) = run_on_both_backends("println(\"a\"); let mut i = 0; fn f() { i += 1; [1][i]; } f();");
+    assert_eq!(out, "a\n");
+    assert_eq!(res, Err(RuntimeErr::IndexOutOfBounds(1, 1)));
+
+    let (_, res) = run_on_both_backends("let mut d = 0; fn f() {} fn f() { d += 1; if d < 10 { f(); }; exit(2); } f();");
+    assert_eq!(res, Err(RuntimeErr::Exit(2)));
+}
+
+#[test]
+fn negative_and_fractional_indexes_are_runtime_errors() {
+    let (_, res) = run_on_both_backends("let i = 0 - 1; [1, 2][i];");
+    assert_eq!(res, Err(RuntimeErr::InvalidIndex(-1.0)));
+
+    let (_, res) = run_on_both_backends("\"ab\"[1.5];");
+    assert_eq!(res, Err(RuntimeErr::InvalidIndex(1.5)));
+}
+
+#[test]
+fn fractional_and_non_finite_exit_codes_are_runtime_errors() {
+    let (_, res) = run_on_both_backends("exit(2.5);");
+    assert_eq!(res, Err(RuntimeErr::InvalidExitCode(2.5)));
+
+    let (_, res) = run_on_both_backends("exit(1 / 0);");
+    assert_eq!(res, Err(RuntimeErr::InvalidExitCode(f64::INFINITY)));
+
+    let (_, res) = run_on_both_backends("exit(0 - 3);");
+    assert_eq!(res, Err(RuntimeErr::Exit(-3)));
+}
+
+#[test]
+fn variables_resolve_to_their_declaring_scope() {
+    let (out, res) = run_on_both_backends("
+        let x = \"global\";
+        fn outer() {
+            let x = \"outer\";
+            fn inner() { x; }
+            for i in 0..1 { println(inner()); };
+            x = \"assigned\";
+            println(x);
+        }
+        outer();
+        println(x);
+
+        let mut n = 0;
+        for i in 0..3 {
+            let y = i;
+            if y == 1 { let y = 10; n += y; };
+            n += y;
+        };
+        println(str(n));
+    ");
+
+    assert_eq!(out, "outer\nassigned\nglobal\n13\n");
+    assert_eq!(res, Ok(()));
+}
+
+#[test]
+fn assignments_change_the_innermost_variable() {
+    let (out, res) = run_on_both_backends("
+        let mut x = \"global\";
+        fn set() { x = \"set\"; }
+        fn append() { x += \"!\"; }
+        fn caller() {
+            let mut x = \"caller\";
+            set();
+            append();
+            println(x);
+        }
+        caller();
+        println(x);
+
+        if true { let mut x = \"block\"; x = \"assigned\"; append(); println(x); };
+        println(x);
+    ");
+
+    assert_eq!(out, "set!\nglobal\nassigned!\nglobal\n");
+    assert_eq!(res, Ok(()));
+}
+
+#[test]
+fn functions_see_the_variables_of_their_caller() {
+    let (out, res) = run_on_both_backends("
+        let m = 1;
+        fn h() { m; }
+        println(str(h()));
+        if true { let m = 2; println(str(h())); };
+        fn g() { let m = 3; h(); }
+        println(str(g()));
+    ");
+
+    assert_eq!(out, "1\n2\n3\n");
+    assert_eq!(res, Ok(()));
+}
+
+#[test]
+fn appending_to_a_shared_string_copies_it() {
+    let (out, res) = run_on_both_backends("
+        let a = \"x\";
+        let mut b = a;
+        b += \"y\";
+        fn shout(s: String) { let mut s = s; s += \"!\"; s; }
+        println(format(\"{} {} {} {}\", a, b, shout(b), b));
+    ");
+
+    assert_eq!(out, "x xy xy! xy\n");
+    assert_eq!(res, Ok(()));
+}
+
+/// Runs the program optimized at every level on both backends, checking it always behaves like
+/// the unoptimized program
+fn run_at_every_opt_level(buf: &str) -> (String, Result<(), RuntimeErr>) {
+    let unoptimized = run_on_both_backends(buf);
+
+    for level in [OptLevel::Basic, OptLevel::Full] {
+        let mut statements = parse(buf);
+        optimize(&mut statements, level);
+        assert_eq!(run_statements_on_both_backends(&statements), unoptimized, "{:?}", level);
+    }
+
+    unoptimized
+}
+
+#[test]
+fn optimizing_keeps_the_output() {
+    let (out, res) = run_at_every_opt_level("
+        let a = 2 * 3 + 1;
+        let greeting = \"hi\" + \" there\";
+        let n = 5;
+        let mut total = 0;
+        for i in 0..n { total += i * a; };
+        if n > 10 { println(\"never\"); };
+        if 1 == 1 { println(greeting); };
+
+        let b = 1;
+        b = 4;
+        println(str(b));
+
+        let c = 1;
+        fn show() { println(str(c)); }
+        let c = 2;
+        show();
+
+        fn shadow(n: Number) { let a = n; str(a); }
+        println(shadow(9));
+        println(format(\"{} {}\", total, a));
+    ");
+
+    assert_eq!(out, "hi there\n4\n2\n9\n70 7\n");
+    assert_eq!(res, Ok(()));
+
+    let (out, res) = run_at_every_opt_level("let zero = 0; let i = 3; println(str(i / zero)); [1][i - 2];");
+    assert_eq!(out, "inf\n");
+    assert_eq!(res, Err(RuntimeErr::IndexOutOfBounds(1, 1)));
+}
+
+#[test]
+fn constants_are_folded_and_dead_branches_removed() {
+    let buf = "let x = 2 * 3 + 1; if 2 < 1 { println(\"no\"); }; let y = x;";
+    let is_number = |statement: &Statement, n: f64| matches!(statement,
+        Statement::VariableAssignment(var_assign) if matches!(var_assign.init_value, Expr::Literal(ExprLiteral { data: Data::Number(val) }) if val == n)
+    );
+
+    let mut statements = parse(buf);
+    optimize(&mut statements, OptLevel::Basic);
+
+    assert!(is_number(&statements[0], 7.0));
+    assert!(matches!(statements[1], Statement::Expr(Expr::Literal(ExprLiteral { data: Data::TempNil }))));
+    assert!(!is_number(&statements[2], 7.0));
+
+    let mut statements = parse(buf);
+    optimize(&mut statements, OptLevel::Full);
+    assert!(is_number(&statements[2], 7.0));
+
+    let mut statements = parse(buf);
+    optimize(&mut statements, OptLevel::None);
+    assert!(!is_number(&statements[0], 7.0));
+}
+
+#[test]
+fn config_parses_opt_level() {
+    assert_eq!(config(&["-e", "1;"]).unwrap().opt_level, OptLevel::None);
+    assert_eq!(config(&["--opt-level", "2", "-e", "1;"]).unwrap().opt_level, OptLevel::Full);
+    assert_eq!(config(&["--opt-level=1", "-e", "1;"]).unwrap().opt_level, OptLevel::Basic);
+    assert!(config(&["--opt-level", "3"]).is_err());
+}
+
+#[test]
+fn dumping_the_syntax_tree() {
+    let statements = parse("let x = 1 + 2; fn f(a: Number) { a; }; if x == 3 { println(\"three\"); };");
+
+    assert_eq!(dump_ast(&statements, AstFormat::Text), "\
+Let name=x mut=false slot=global
+  Binary op=Add
+    Literal value=1
+    Literal value=2
+Fn name=f args=a: Number returns=Number
+  Block
+    Field name=a slot=local 0:0
+If
+  Binary op=Eq
+    Field name=x slot=global
+    Literal value=3
+  Block
+    Call args=1
+      Field name=println slot=global
+      Literal value=\"three\"
+");
+
+    let json = dump_ast(&parse("let x = 1;"), AstFormat::Json);
+    assert_eq!(json, "[{\"kind\": \"Let\", \"name\": \"x\", \"mut\": false, \"slot\": \"global\", \
+        \"children\": [{\"kind\": \"Literal\", \"value\": 1, \"children\": []}]}]\n");
+
+    let json = dump_ast(&parse("[\"a\"]; true;"), AstFormat::Json);
+    assert_eq!(json, "[{\"kind\": \"List\", \"len\": 1, \"children\": [\
+        {\"kind\": \"Literal\", \"value\": \"a\", \"children\": []}]}, \
+        {\"kind\": \"Literal\", \"value\": true, \"children\": []}]\n");
+}
+
+#[test]
+fn dumping_tokens_shows_their_positions() {
+    let tokens = tokenize("test.ox", "let x = 1;\nx;").unwrap();
+    let dumped = dump_tokens(&tokens);
+    let lines: Vec<&str> = dumped.lines().collect();
+
+    assert_eq!(lines[0], "1:1      Let");
+    assert_eq!(lines[1], "1:5      Identifier(\"x\")");
+    assert_eq!(lines[5], "2:1      Identifier(\"x\")");
+}
+
+#[test]
+fn config_parses_dump_flags() {
+    let config_default = config(&["-e", "1;"]).unwrap();
+    assert!(!config_default.dump_tokens);
+    assert_eq!(config_default.dump_ast, None);
+
+    let config_dump = config(&["--dump-tokens", "--dump-ast=json", "-e", "1;"]).unwrap();
+    assert!(config_dump.dump_tokens);
+    assert_eq!(config_dump.dump_ast, Some(AstFormat::Json));
+
+    assert_eq!(config(&["--dump-ast", "-e", "1;"]).unwrap().dump_ast, Some(AstFormat::Text));
+    assert!(config(&["--dump-ast=xml", "-e", "1;"]).is_err());
+}
@@ -0,0 +1,97 @@
+use crate::errors::ParseErr;
+
+use super::token::{Token, TokenPos};
+use super::token_type::TokenType;
+
+/// Lazily lexes a whole source buffer into tokens, ending with a single `EOF` token.
+/// Lexing stops after the first error.
+pub struct Lexer<'b> {
+    filename: String,
+    buf: &'b str,
+    byte_i: usize,
+    line_i: usize,
+    col_i: usize,
+    keep_comments: bool,
+    finished: bool
+}
+
+impl<'b> Lexer<'b> {
+    pub fn new(filename: &str, buf: &'b str) -> Self {
+        Self {
+            filename: filename.to_string(),
+            buf,
+            byte_i: 0,
+            line_i: 0,
+            col_i: 0,
+            keep_comments: false,
+            finished: false
+        }
+    }
+
+    /// Emits `Comment` tokens instead of skipping them, used by the formatter
+    pub fn keep_comments(mut self) -> Self {
+        self.keep_comments = true;
+        self
+    }
+
+    fn token_pos(&self) -> TokenPos {
+        TokenPos::new(self.line_i, self.col_i, self.filename.to_string())
+    }
+
+    /// Moves past `byte_len` bytes of the buffer, keeping track of lines and columns
+    fn advance(&mut self, byte_len: usize) {
+        let skipped = &self.buf[self.byte_i..self.byte_i + byte_len];
+
+        for c in skipped.chars() {
+            if c == '\n' {
+                self.line_i += 1;
+                self.col_i = 0;
+            } else {
+                self.col_i += 1;
+            }
+        }
+
+        self.byte_i += byte_len;
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.buf[self.byte_i..];
+        let trimmed = rest.trim_start();
+
+        self.advance(rest.len() - trimmed.len());
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Result<Token, ParseErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        loop {
+            self.skip_whitespace();
+
+            let token_pos = self.token_pos();
+            let start = self.byte_i;
+
+            if start == self.buf.len() {
+                self.finished = true;
+                return Some(Ok(Token::new(token_pos, TokenType::EOF, (start, start))));
+            }
+
+            match TokenType::next_token_type(&self.buf[start..]) {
+                Ok((TokenType::Comment, byte_len)) if !self.keep_comments => self.advance(byte_len),
+                Ok((token_type, byte_len)) => {
+                    self.advance(byte_len);
+                    return Some(Ok(Token::new(token_pos, token_type, (start, self.byte_i))));
+                },
+                Err(err_kind) => {
+                    self.finished = true;
+                    return Some(Err(err_kind.to_err(token_pos)));
+                }
+            }
+        }
+    }
+}
@@ -1,14 +1,17 @@
+use unicode_ident::{is_xid_continue, is_xid_start};
+
 use crate::errors::ParseErrKind;
 
-use super::{token::TokenPos, token_type::TokenType};
+use super::token_type::TokenType;
 
 impl TokenType {
-    pub fn next_token_type(token_pos: TokenPos, buf: &str) -> Option<(TokenType, usize)> {
+    /// Lexes the token at the start of `buf`, returning it along with its length in bytes
+    pub fn next_token_type(buf: &str) -> Result<(TokenType, usize), ParseErrKind> {
         let mut chars = buf.chars();
 
         let mut token_char_len = 1;
         let mut do_decrease_len = false;
-        let first_char = chars.next()?;
+        let first_char = chars.next().expect("tried to lex a token from an empty buffer");
 
         let mut next_char = || {
             token_char_len += 1;
@@ -40,6 +43,22 @@ impl TokenType {
                 Some('=') => Self::StarEqual,
                 _ => decrease_len(Self::Star),
             },
+            '/' if buf.starts_with("///") && !buf.starts_with("////") => {
+                let comment_char_len = scan_line_comment(buf);
+                token_char_len = comment_char_len;
+
+                let doc = &buf[3..buf.find('\n').unwrap_or(buf.len())];
+                let doc = doc.strip_prefix(' ').unwrap_or(doc);
+                Self::DocComment(doc.trim_end().to_string())
+            },
+            '/' if buf.starts_with("//") => {
+                token_char_len = scan_line_comment(buf);
+                Self::Comment
+            },
+            '/' if buf.starts_with("/*") => {
+                token_char_len = scan_block_comment(buf)?;
+                Self::Comment
+            },
             '/' => match next_char() {
                 Some('=') => Self::SlashEqual,
                 _ => decrease_len(Self::Slash),
@@ -63,7 +82,10 @@ impl TokenType {
                 _ => decrease_len(Self::Less),
             },
 
-            ':' => Self::Colon,
+            ':' => match next_char() {
+                Some(':') => Self::DoubleColon,
+                _ => decrease_len(Self::Colon),
+            },
             ';' => Self::Semicolon,
             '.' => match next_char() {
                 Some('.') => Self::Spread,
@@ -72,27 +94,25 @@ impl TokenType {
             ',' => Self::Comma,
 
             '"' => {
-                let mut current_char = next_char();
-                while current_char.is_some() && current_char != Some('"') {
-                    current_char = next_char();
-                }
+                let (string, string_char_len) = scan_string(&buf[1..])?;
 
-                if current_char.is_none() {
-                    // TODO: we gotta handle this sometime later
-                    ParseErrKind::UnmatchedDelimiter('"')
-                        .to_err(token_pos)
-                        .report();
-                }
+                token_char_len += string_char_len;
+                Self::String(string)
+            }
 
-                let string = &buf[1..token_char_len - 1].to_string();
-                Self::String(string.to_string())
+            'r' if raw_string_hashes(&buf[1..]).is_some() => {
+                let hashes = raw_string_hashes(&buf[1..]).unwrap();
+                let (string, string_char_len) = scan_raw_string(&buf[hashes + 2..], hashes)?;
+
+                token_char_len += hashes + 1 + string_char_len;
+                Self::String(string)
             }
 
             _ => {
-                if first_char.is_digit(10) {
+                if first_char.is_ascii_digit() {
                     let mut current_char = next_char();
                     let mut has_decimal_point = false;
-                    while current_char.is_some() && current_char.unwrap().is_digit(10)
+                    while current_char.is_some() && current_char.unwrap().is_ascii_digit()
                         || current_char == Some('.')
                     {
                         if current_char == Some('.') {
@@ -113,24 +133,22 @@ impl TokenType {
 
                     let number: &f64 = &buf[..token_char_len].parse().unwrap();
                     Self::Number(*number)
-                } else if first_char.is_ascii_alphabetic() || first_char == '_' || first_char == '$'
-                {
+                } else if is_ident_start(first_char) {
                     let mut current_char = next_char();
-                    while current_char.is_some() && current_char.unwrap().is_ascii_alphabetic()
-                        || current_char == Some('_')
-                        || current_char == Some('$')
-                    {
+                    while current_char.is_some_and(is_ident_continue) {
                         current_char = next_char();
                     }
 
                     token_char_len -= 1;
 
-                    let name = &buf[..token_char_len];
+                    let name = &buf[..byte_len(buf, token_char_len)];
                     match name {
                         "fn" => Self::Fn,
                         "let" => Self::Let,
                         "mut" => Self::Mut,
                         "mod" => Self::Mod,
+                        "pub" => Self::Pub,
+                        "use" => Self::Use,
                         "for" => Self::For,
                         "in" => Self::In,
                         "if" => Self::If,
@@ -141,10 +159,7 @@ impl TokenType {
                         _ => Self::Identifier(name.to_string()),
                     }
                 } else {
-                    // TODO: we gotta handle this sometime later
-                    ParseErrKind::UnexpectedChar(first_char)
-                        .to_err(token_pos)
-                        .report();
+                    return Err(ParseErrKind::UnexpectedChar(first_char));
                 }
             }
         };
@@ -153,6 +168,131 @@ impl TokenType {
             token_char_len -= 1;
         }
 
-        Some((token_type, token_char_len))
+        Ok((token_type, byte_len(buf, token_char_len)))
+    }
+}
+
+/// Converts a length in chars from the start of `buf` to a length in bytes
+fn byte_len(buf: &str, char_len: usize) -> usize {
+    buf.char_indices()
+        .nth(char_len)
+        .map_or(buf.len(), |(byte_i, _)| byte_i)
+}
+
+/// Scans the contents of a string literal, `buf` starting right after the opening quote.
+/// Returns the unescaped string and the char length including the closing quote.
+fn scan_string(buf: &str) -> Result<(String, usize), ParseErrKind> {
+    let mut string = String::new();
+    let mut chars = buf.chars();
+    let mut char_len = 0;
+
+    let mut next_char = || {
+        char_len += 1;
+        chars.next()
+    };
+
+    loop {
+        match next_char() {
+            Some('"') => break,
+            Some('\\') => {
+                let escaped = match next_char() {
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some('r') => '\r',
+                    Some('0') => '\0',
+                    Some('\\') => '\\',
+                    Some('"') => '"',
+                    Some('u') => {
+                        if next_char() != Some('{') {
+                            return Err(ParseErrKind::InvalidUnicodeEscape());
+                        }
+
+                        let mut hex = String::new();
+                        loop {
+                            match next_char() {
+                                Some('}') => break,
+                                Some(c) if c.is_ascii_hexdigit() && hex.len() < 6 => hex.push(c),
+                                _ => return Err(ParseErrKind::InvalidUnicodeEscape())
+                            }
+                        }
+
+                        u32::from_str_radix(&hex, 16).ok()
+                            .and_then(char::from_u32)
+                            .ok_or(ParseErrKind::InvalidUnicodeEscape())?
+                    },
+                    Some(c) => return Err(ParseErrKind::UnknownEscape(c)),
+                    None => return Err(ParseErrKind::UnmatchedDelimiter('"'))
+                };
+
+                string.push(escaped);
+            },
+            Some(c) => string.push(c),
+            None => return Err(ParseErrKind::UnmatchedDelimiter('"'))
+        }
     }
+
+    Ok((string, char_len))
+}
+
+/// Counts the `#`s between the `r` and the opening quote of a raw string, `None` if `buf` isn't
+/// the start of a raw string
+fn raw_string_hashes(buf: &str) -> Option<usize> {
+    let hashes = buf.chars().take_while(|c| *c == '#').count();
+    buf[hashes..].starts_with('"').then_some(hashes)
+}
+
+/// Scans the contents of a raw string literal, `buf` starting right after the opening quote.
+/// Returns the string as written and the char length including the closing quote and hashes.
+fn scan_raw_string(buf: &str, hashes: usize) -> Result<(String, usize), ParseErrKind> {
+    let terminator = format!("\"{}", "#".repeat(hashes));
+
+    let end = buf.find(&terminator)
+        .ok_or(ParseErrKind::UnmatchedDelimiter('"'))?;
+    let string = &buf[..end];
+
+    Ok((string.to_string(), string.chars().count() + terminator.len()))
+}
+
+/// Returns the char length of a `//` comment, which runs until the end of the line
+fn scan_line_comment(buf: &str) -> usize {
+    buf.chars().take_while(|c| *c != '\n').count()
+}
+
+/// Returns the char length of a `/* */` comment, block comments can be nested
+fn scan_block_comment(buf: &str) -> Result<usize, ParseErrKind> {
+    let mut chars = buf.chars().peekable();
+    let mut char_len = 0;
+    let mut depth = 0;
+
+    while let Some(c) = chars.next() {
+        char_len += 1;
+
+        match (c, chars.peek()) {
+            ('/', Some('*')) => {
+                chars.next();
+                char_len += 1;
+                depth += 1;
+            },
+            ('*', Some('/')) => {
+                chars.next();
+                char_len += 1;
+                depth -= 1;
+
+                if depth == 0 {
+                    return Ok(char_len);
+                }
+            },
+            _ => ()
+        }
+    }
+
+    Err(ParseErrKind::UnterminatedBlockComment())
+}
+
+fn is_ident_start(c: char) -> bool {
+    is_xid_start(c) || c == '_' || c == '$'
+}
+
+fn is_ident_continue(c: char) -> bool {
+    is_xid_continue(c) || c == '$'
 }
@@ -0,0 +1,133 @@
+use crate::errors::ParseErrKind;
+
+use super::{tokenize, Lexer};
+use super::token_type::TokenType;
+
+fn tokenize_string(buf: &str) -> String {
+    let tokens = tokenize("test.ox", buf).unwrap();
+
+    match &tokens[0].token {
+        TokenType::String(string) => string.to_string(),
+        token => panic!("expected a string token, got {:?}", token)
+    }
+}
+
+#[test]
+fn string_escapes() {
+    assert_eq!(tokenize_string(r#""a\nb\tc\\d\"e""#), "a\nb\tc\\d\"e");
+}
+
+#[test]
+fn string_unicode_escape() {
+    assert_eq!(tokenize_string(r#""\u{48}\u{1F600}""#), "H\u{1F600}");
+}
+
+#[test]
+fn string_unknown_escape() {
+    let err = tokenize("test.ox", r#""\q""#).unwrap_err();
+    assert_eq!(err.err_kind, ParseErrKind::UnknownEscape('q'));
+}
+
+#[test]
+fn string_invalid_unicode_escape() {
+    let err = tokenize("test.ox", r#""\u{D800}""#).unwrap_err();
+    assert_eq!(err.err_kind, ParseErrKind::InvalidUnicodeEscape());
+}
+
+#[test]
+fn raw_string() {
+    assert_eq!(tokenize_string(r#"r"C:\path\n""#), r"C:\path\n");
+    assert_eq!(tokenize_string(r##"r#"say "hi""#"##), r#"say "hi""#);
+}
+
+#[test]
+fn raw_prefix_is_still_an_identifier() {
+    let tokens = tokenize("test.ox", "r + rest").unwrap();
+    assert!(matches!(&tokens[0].token, TokenType::Identifier(name) if name == "r"));
+    assert!(matches!(&tokens[2].token, TokenType::Identifier(name) if name == "rest"));
+}
+
+#[test]
+fn multi_line_string() {
+    let tokens = tokenize("test.ox", "\"first\nsecond\" x").unwrap();
+    assert!(matches!(&tokens[0].token, TokenType::String(string) if string == "first\nsecond"));
+
+    let x_pos = &tokens[1].token_pos;
+    assert_eq!((x_pos.line_i, x_pos.col_i), (1, 8));
+}
+
+#[test]
+fn comments_are_skipped() {
+    let tokens = tokenize("test.ox", "a // line\n/* block /* nested */ still */ b / c").unwrap();
+    let tokens: Vec<_> = tokens.iter().map(|token| format!("{:?}", token.token)).collect();
+
+    assert_eq!(tokens, [
+        "Identifier(\"a\")",
+        "Identifier(\"b\")",
+        "Slash",
+        "Identifier(\"c\")",
+        "EOF"
+    ]);
+}
+
+#[test]
+fn unterminated_block_comment() {
+    let err = tokenize("test.ox", "/* /* */").unwrap_err();
+    assert_eq!(err.err_kind, ParseErrKind::UnterminatedBlockComment());
+}
+
+#[test]
+fn doc_comments() {
+    let tokens = tokenize("test.ox", "/// adds things\n//// not a doc\nfn").unwrap();
+    assert!(matches!(&tokens[0].token, TokenType::DocComment(doc) if doc == "adds things"));
+    assert!(matches!(&tokens[1].token, TokenType::Fn));
+}
+
+#[test]
+fn identifiers_with_digits_and_unicode() {
+    let tokens = tokenize("test.ox", "x1 sum_2 línea $tmp 9x").unwrap();
+    let tokens: Vec<_> = tokens.iter().map(|token| format!("{:?}", token.token)).collect();
+
+    assert_eq!(tokens, [
+        "Identifier(\"x1\")",
+        "Identifier(\"sum_2\")",
+        "Identifier(\"línea\")",
+        "Identifier(\"$tmp\")",
+        "Number(9.0)",
+        "Identifier(\"x\")",
+        "EOF"
+    ]);
+}
+
+#[test]
+fn columns_count_chars_not_bytes() {
+    let tokens = tokenize("test.ox", "let ñandú = \"é\"; x").unwrap();
+    let cols: Vec<_> = tokens.iter().map(|token| token.token_pos.col_i).collect();
+
+    assert_eq!(cols, [0, 4, 10, 12, 15, 17, 18]);
+}
+
+#[test]
+fn token_spans() {
+    let buf = "let x =\n  \"a\nb\";";
+    let tokens = tokenize("test.ox", buf).unwrap();
+    let spans: Vec<_> = tokens.iter().map(|token| &buf[token.span.0..token.span.1]).collect();
+
+    assert_eq!(spans, ["let", "x", "=", "\"a\nb\"", ";", ""]);
+
+    let semicolon_pos = &tokens[4].token_pos;
+    assert_eq!((semicolon_pos.line_i, semicolon_pos.col_i), (2, 2));
+}
+
+#[test]
+fn lex_errors_are_values() {
+    let mut lexer = Lexer::new("test.ox", "a # b");
+
+    assert!(matches!(lexer.next(), Some(Ok(token)) if matches!(token.token, TokenType::Identifier(_))));
+
+    let err = lexer.next().unwrap().unwrap_err();
+    assert_eq!(err.err_kind, ParseErrKind::UnexpectedChar('#'));
+    assert_eq!(err.token_pos.col_i, 2);
+
+    assert!(lexer.next().is_none());
+}
@@ -25,20 +25,18 @@ impl fmt::Display for TokenPos {
     }
 }
 
+/// Byte offsets `(start, end)` of a token in the source buffer
+pub type Span = (usize, usize);
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub token_pos: TokenPos,
     pub token: TokenType,
+    pub span: Span,
 }
 
 impl Token {
-    pub fn new(token_pos: TokenPos, token: TokenType) -> Self {
-        Self { token_pos, token }
-    }
-
-    pub fn next_token(token_pos: TokenPos, buf: &str) -> Option<(usize, Self)> {
-        let next_token_data = TokenType::next_token_type(token_pos.clone(), buf)?;
-
-        Some((next_token_data.1, Self::new(token_pos, next_token_data.0)))
+    pub fn new(token_pos: TokenPos, token: TokenType, span: Span) -> Self {
+        Self { token_pos, token, span }
     }
 }
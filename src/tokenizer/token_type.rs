@@ -1,3 +1,5 @@
+use std::rc::Rc;
+
 use crate::data::Data;
 use crate::expressions::assign::AssignOp;
 use crate::operations::Operation;
@@ -32,6 +34,7 @@ pub enum TokenType {
     LessEqual,
 
     Colon,
+    DoubleColon,
     Semicolon,
     Dot,
     Spread,
@@ -41,6 +44,8 @@ pub enum TokenType {
     Let,
     Mut,
     Mod,
+    Pub,
+    Use,
     For,
     In,
     If,
@@ -52,13 +57,16 @@ pub enum TokenType {
     Number(f64),
     Bool(bool),
 
+    Comment,
+    DocComment(String),
+
     EOF,
 }
 
 impl TokenType {
     pub fn to_data(&self) -> Option<Data> {
         match self {
-            Self::String(val) => Some(Data::String(val.to_string())),
+            Self::String(val) => Some(Data::String(Rc::new(val.to_string()))),
             Self::Number(val) => Some(Data::Number(*val)),
             Self::Bool(val) => Some(Data::Bool(*val)),
             _ => None
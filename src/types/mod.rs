@@ -1,32 +1,51 @@
 use crate::builtin::BuiltinFn;
+use crate::engine::HostTypeId;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Type {
     String,
     Number,
     Bool,
+    Option(Box<Type>),
+    /// Value of a fallible operation, failing with an error message
+    Result(Box<Type>),
+    List(Box<Type>),
+    /// Named fields, like the `{ status, stdout, stderr }` returned by `run`
+    Record(Vec<(String, Type)>),
     Fn {
         args_types: Vec<(String, Type)>,
         return_type: Box<Type>
     },
     BuiltinFn(BuiltinFn),
+    /// A Rust type registered by the embedding application, see [`crate::engine::CustomType`]
+    Host(HostTypeId),
     TempNil
 }
 
 impl Type {
-    pub fn get_name(&self) -> Option<String> {
+    pub fn get_name(&self) -> String {
         match self {
-            Self::String => Some(String::from("String")),
-            Self::Number => Some(String::from("Number")),
-            Self::Bool => Some(String::from("Bool")),
-            Self::Fn { args_types: _args, return_type: _ } => Some(String::from("Fn")),
-            Self::BuiltinFn(_) => Some(String::from("Builtin")),
-            Self::TempNil => Some(String::from("temporary nil val"))
+            Self::String => String::from("String"),
+            Self::Number => String::from("Number"),
+            Self::Bool => String::from("Bool"),
+            Self::Option(inner) => format!("Option<{}>", inner.get_name()),
+            Self::Result(inner) => format!("Result<{}>", inner.get_name()),
+            Self::List(item_type) => format!("List<{}>", item_type.get_name()),
+            Self::Record(fields) => {
+                let fields: Vec<_> = fields.iter()
+                    .map(|(name, field_type)| format!("{}: {}", name, field_type.get_name()))
+                    .collect();
+                format!("{{ {} }}", fields.join(", "))
+            },
+            Self::Fn { args_types: _args, return_type: _ } => String::from("Fn"),
+            Self::BuiltinFn(_) => String::from("Builtin"),
+            Self::Host(id) => id.name.to_string(),
+            Self::TempNil => String::from("temporary nil val")
         }
     }
 
-    pub fn from_name(name: &String) -> Option<Type> {
-        match &name[..] {
+    pub fn from_name(name: &str) -> Option<Type> {
+        match name {
             "String" => Some(Self::String),
             "Number" => Some(Self::Number),
             "Bool" => Some(Self::Bool),
@@ -34,4 +53,14 @@ impl Type {
             _ => None
         }
     }
+
+    /// Whether values of this type can be turned into a string by builtins like `str` and `print`
+    pub fn is_displayable(&self) -> bool {
+        match self {
+            Self::String | Self::Number | Self::Bool => true,
+            Self::Option(inner) | Self::Result(inner) | Self::List(inner) => inner.is_displayable(),
+            Self::Record(fields) => fields.iter().all(|(_, field_type)| field_type.is_displayable()),
+            _ => false
+        }
+    }
 }
@@ -0,0 +1,88 @@
+use crate::expressions::Expr;
+use crate::expressions::block::ExprBlock;
+use crate::statements::Statement;
+
+/// Walks the syntax tree of a parsed program. Each method visits the children of its node by
+/// default, so an implementation only overrides the nodes it cares about and calls the matching
+/// `walk_*` function to keep going.
+pub trait Visitor {
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement);
+    }
+
+    /// The body of a function, `for` or `if`, blocks used as expressions go through `visit_expr`
+    fn visit_block(&mut self, block: &ExprBlock) {
+        walk_block(self, block);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr);
+    }
+}
+
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &Statement) {
+    match statement {
+        Statement::VariableAssignment(var_assign) => visitor.visit_expr(&var_assign.init_value),
+        Statement::FunctionDeclaration(fn_decl) => visitor.visit_block(&fn_decl.body),
+        Statement::ModuleImport(mod_import) => {
+            for statement in mod_import.statements.iter() {
+                visitor.visit_statement(statement);
+            }
+        },
+        Statement::UseDeclaration(_) => (),
+        Statement::Expr(expr) => visitor.visit_expr(expr)
+    }
+}
+
+pub fn walk_block<V: Visitor + ?Sized>(visitor: &mut V, block: &ExprBlock) {
+    for statement in block.body.iter() {
+        visitor.visit_statement(statement);
+    }
+}
+
+/// Visits the parts of an expression in the order they're written in
+pub fn walk_expr<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expr) {
+    match expr {
+        Expr::Literal(_) => (),
+        Expr::Field(field_expr) => {
+            if let Some(child) = &field_expr.child {
+                visitor.visit_expr(child);
+            }
+        },
+        Expr::Binary(binary_expr) => {
+            visitor.visit_expr(&binary_expr.lhs);
+            visitor.visit_expr(&binary_expr.rhs);
+        },
+        Expr::Index(index_expr) => {
+            visitor.visit_expr(&index_expr.value);
+            visitor.visit_expr(&index_expr.index);
+        },
+        Expr::Unary(unary_expr) => visitor.visit_expr(&unary_expr.value),
+        Expr::Call(call_expr) => {
+            visitor.visit_expr(&call_expr.path);
+            for arg_expr in call_expr.args.iter() {
+                visitor.visit_expr(arg_expr);
+            }
+        },
+        Expr::Assign(assign_expr) => {
+            visitor.visit_expr(&assign_expr.lhs);
+            visitor.visit_expr(&assign_expr.rhs);
+        },
+        Expr::Block(block_expr) => walk_block(visitor, block_expr),
+        Expr::For(for_expr) => {
+            visitor.visit_expr(&for_expr.start_i);
+            visitor.visit_expr(&for_expr.end_i);
+            visitor.visit_block(&for_expr.body);
+        },
+        Expr::If(if_expr) => {
+            visitor.visit_expr(&if_expr.condition_expr);
+            visitor.visit_block(&if_expr.body);
+        },
+        Expr::List(list_expr) => {
+            for item in list_expr.items.iter() {
+                visitor.visit_expr(item);
+            }
+        },
+        Expr::Member(member_expr) => visitor.visit_expr(&member_expr.value)
+    }
+}
@@ -0,0 +1,321 @@
+use std::rc::Rc;
+
+use crate::data::Data;
+use crate::expressions::{Evaluable, Expr};
+use crate::expressions::block::ExprBlock;
+use crate::expressions::assign::AssignOp;
+use crate::expressions::path::ExprField;
+use crate::operations::Operation;
+use crate::statements::Statement;
+use crate::statements::r#fn::FunctionDeclaration;
+use crate::types::Type;
+use super::{Chunk, Function, Instruction, Local, Variable};
+
+/// Compiles a parsed program to bytecode, declarations in it are run like they would be by the
+/// tree walking interpreter. Variables declared outside of functions and blocks go in the
+/// interpreter's memory, all others get a slot in the frame of the function or program.
+pub fn compile(statements: &[Statement]) -> Rc<Function> {
+    let mut compiler = Compiler::default();
+
+    for statement in statements.iter() {
+        compiler.statement(statement);
+    }
+
+    compiler.constant(Data::TempNil);
+    compiler.emit(Instruction::Return);
+
+    Rc::new(Function {
+        name: String::from("<main>"),
+        args: vec![],
+        return_type: Type::TempNil,
+        chunk: compiler.chunk
+    })
+}
+
+fn compile_fn(fn_decl: &FunctionDeclaration) -> Function {
+    let mut compiler = Compiler {
+        in_function: true,
+        ..Default::default()
+    };
+
+    // the body shares its scope with the arguments, which are in the first slots
+    compiler.begin_scope();
+    for (arg_name, _) in fn_decl.args.iter() {
+        compiler.declare(arg_name, 0);
+    }
+    compiler.block(&fn_decl.body);
+    // the slots of the outermost scope are dropped with the frame on return
+    compiler.end_scope(false);
+    compiler.emit(Instruction::Return);
+
+    Function {
+        name: fn_decl.name.to_string(),
+        args: fn_decl.args.clone(),
+        return_type: *fn_decl.return_type.clone(),
+        chunk: compiler.chunk
+    }
+}
+
+/// A block being compiled
+struct Scope {
+    /// Names declared in the block, each stored in the slot `first_slot` plus its index
+    names: Vec<String>,
+    first_slot: usize,
+    /// Index into the chunk's locals of each name
+    locals: Vec<usize>
+}
+
+#[derive(Default)]
+struct Compiler {
+    chunk: Chunk,
+    /// Blocks enclosing the code being compiled, innermost last. Outside of functions the
+    /// outermost scope is the global scope of the interpreter's memory, which isn't in here.
+    scopes: Vec<Scope>,
+    in_function: bool
+}
+
+impl Compiler {
+    /// Appends an instruction, returning where it is so jumps can be patched
+    fn emit(&mut self, instruction: Instruction) -> usize {
+        self.chunk.code.push(instruction);
+        self.chunk.code.len() - 1
+    }
+
+    fn constant(&mut self, data: Data) {
+        self.chunk.constants.push(data);
+        self.emit(Instruction::Constant(self.chunk.constants.len() - 1));
+    }
+
+    fn name(&mut self, name: &str) -> usize {
+        match self.chunk.names.iter().position(|known| known == name) {
+            Some(i) => i,
+            None => {
+                self.chunk.names.push(name.to_string());
+                self.chunk.names.len() - 1
+            }
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        let first_slot = self.scopes.last()
+            .map(|scope| scope.first_slot + scope.names.len())
+            .unwrap_or(0);
+
+        self.scopes.push(Scope { names: vec![], first_slot, locals: vec![] });
+    }
+
+    /// Closes the innermost block, emptying its slots if `clear` so they don't keep their
+    /// values alive and can be reused
+    fn end_scope(&mut self, clear: bool) {
+        let scope = self.scopes.pop().unwrap();
+
+        let end = self.chunk.code.len();
+        for &local in scope.locals.iter() {
+            self.chunk.locals[local].end = end;
+        }
+
+        if clear && !scope.names.is_empty() {
+            self.emit(Instruction::ClearLocals(scope.first_slot, scope.names.len()));
+        }
+    }
+
+    /// Declares a variable in the innermost block, live from the instruction at `start`, and
+    /// returns its slot. Redeclaring a name in the same block keeps its slot.
+    fn declare(&mut self, name: &str, start: usize) -> usize {
+        let scope = self.scopes.last_mut().expect("there should be a block to declare in");
+
+        if let Some(index) = scope.names.iter().position(|declared| declared == name) {
+            return scope.first_slot + index;
+        }
+
+        let slot = scope.first_slot + scope.names.len();
+        scope.names.push(name.to_string());
+        scope.locals.push(self.chunk.locals.len());
+
+        self.chunk.locals.push(Local { name: name.to_string(), slot, start, end: start });
+        self.chunk.slot_count = self.chunk.slot_count.max(slot + 1);
+        slot
+    }
+
+    /// Emits an instruction declaring `name` with the value on top of the stack
+    fn declare_variable(&mut self, name: &str, instruction: fn(Variable) -> Instruction) {
+        let variable = match self.scopes.is_empty() {
+            true => Variable::Global(self.name(name)),
+            // live once the instruction has run
+            false => Variable::Local(self.declare(name, self.chunk.code.len() + 1))
+        };
+
+        self.emit(instruction(variable));
+    }
+
+    /// Where to access a variable used by the code being compiled
+    fn variable(&mut self, field_expr: &ExprField) -> Variable {
+        let name = match field_expr.child {
+            Some(_) => field_expr.mangle_path().unwrap(),
+            None => field_expr.field_name.to_string()
+        };
+
+        for scope in self.scopes.iter().rev() {
+            if let Some(index) = scope.names.iter().position(|declared| *declared == name) {
+                return Variable::Local(scope.first_slot + index);
+            }
+        }
+
+        match self.in_function {
+            true => Variable::Dynamic(self.name(&name)),
+            false => Variable::Global(self.name(&name))
+        }
+    }
+
+    /// Points the jump at `at` to the next instruction to be emitted
+    fn patch_jump(&mut self, at: usize) {
+        let target = self.chunk.code.len();
+
+        match &mut self.chunk.code[at] {
+            Instruction::Jump(jump_target) | Instruction::JumpIfFalse(jump_target) | Instruction::ForNext(_, jump_target) => {
+                *jump_target = target
+            },
+            _ => unreachable!("only jumps can be patched")
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::VariableAssignment(var_assign) => {
+                self.expr(&var_assign.init_value);
+                self.declare_variable(&var_assign.name, Instruction::Let);
+            },
+            Statement::FunctionDeclaration(fn_decl) => {
+                self.constant(Data::VmFn(Rc::new(compile_fn(fn_decl))));
+                self.declare_variable(&fn_decl.name, Instruction::Let);
+            },
+            Statement::ModuleImport(mod_import) => {
+                for statement in mod_import.statements.iter() {
+                    self.statement(statement);
+                }
+            },
+            // paths are resolved while parsing so there's nothing left to do at runtime
+            Statement::UseDeclaration(_) => (),
+            Statement::Expr(expr) => {
+                self.expr(expr);
+                self.emit(Instruction::Pop);
+            }
+        }
+    }
+
+    /// Leaves the value of the block on the stack, that of its last statement if it's an expression
+    fn block(&mut self, block: &ExprBlock) {
+        match block.body.split_last() {
+            Some((Statement::Expr(last_expr), body)) => {
+                for statement in body.iter() {
+                    self.statement(statement);
+                }
+                self.expr(last_expr);
+            },
+            _ => {
+                for statement in block.body.iter() {
+                    self.statement(statement);
+                }
+                self.constant(Data::TempNil);
+            }
+        }
+    }
+
+    /// Leaves the value of the expression on the stack, evaluating its parts in the same order
+    /// as the tree walking interpreter
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Literal(literal_expr) => self.constant(literal_expr.data.clone()),
+            Expr::Binary(binary_expr) => {
+                self.expr(&binary_expr.lhs);
+                self.expr(&binary_expr.rhs);
+                self.emit(Instruction::Binary(binary_expr.operation));
+            },
+            Expr::Index(index_expr) => {
+                self.expr(&index_expr.index);
+                self.expr(&index_expr.value);
+                self.emit(Instruction::Index);
+            },
+            Expr::Unary(_) => unreachable!("the parser doesn't produce unary expressions"),
+            Expr::Field(field_expr) => {
+                let variable = self.variable(field_expr);
+                self.emit(Instruction::Load(variable));
+            },
+            Expr::Call(call_expr) => {
+                self.expr(&call_expr.path);
+                for arg_expr in call_expr.args.iter() {
+                    self.expr(arg_expr);
+                }
+                self.emit(Instruction::Call(call_expr.args.len()));
+            },
+            Expr::Assign(assign_expr) => {
+                self.expr(&assign_expr.rhs);
+
+                match (assign_expr.lhs.as_ref(), &assign_expr.op) {
+                    (Expr::Member(member_expr), op) => {
+                        if let AssignOp::AddEq = op {
+                            self.expr(&assign_expr.lhs);
+                            self.emit(Instruction::Swap);
+                            self.emit(Instruction::Binary(Operation::Add));
+                        }
+
+                        self.expr(&member_expr.value);
+                        let name = self.name(&member_expr.field_name);
+                        self.emit(Instruction::SetMember(name));
+                    },
+                    (Expr::Field(field_expr), AssignOp::Eq) => {
+                        let variable = self.variable(field_expr);
+                        self.emit(Instruction::Assign(variable));
+                    },
+                    (Expr::Field(field_expr), AssignOp::AddEq) => {
+                        let variable = self.variable(field_expr);
+                        self.emit(Instruction::AddAssign(variable));
+                    },
+                    _ => unreachable!("only paths and members can be assigned to")
+                }
+            },
+            Expr::Block(block_expr) => self.block(block_expr),
+            Expr::For(for_expr) => {
+                self.expr(&for_expr.start_i);
+                self.expr(&for_expr.end_i);
+                self.emit(Instruction::ForRange);
+
+                // each time around the loop the body starts with a new scope holding the index
+                self.begin_scope();
+                let index_slot = self.declare(&for_expr.index_var, self.chunk.code.len() + 1);
+                let loop_start = self.emit(Instruction::ForNext(index_slot, 0));
+
+                self.block(&for_expr.body);
+                self.emit(Instruction::Pop);
+                self.end_scope(true);
+                self.emit(Instruction::Jump(loop_start));
+
+                self.patch_jump(loop_start);
+                self.constant(Data::TempNil);
+            },
+            Expr::If(if_expr) => {
+                self.expr(&if_expr.condition_expr);
+                let skip_body = self.emit(Instruction::JumpIfFalse(0));
+
+                self.begin_scope();
+                self.block(&if_expr.body);
+                self.emit(Instruction::Pop);
+                self.end_scope(true);
+
+                self.patch_jump(skip_body);
+                self.constant(Data::TempNil);
+            },
+            Expr::List(list_expr) => {
+                for item in list_expr.items.iter() {
+                    self.expr(item);
+                }
+                self.emit(Instruction::List(list_expr.items.len()));
+            },
+            Expr::Member(member_expr) => {
+                self.expr(&member_expr.value);
+                let name = self.name(&member_expr.field_name);
+                self.emit(Instruction::Member(name));
+            }
+        }
+    }
+}
@@ -0,0 +1,458 @@
+use std::rc::Rc;
+
+use crate::data::Data;
+use crate::errors::RuntimeErr;
+use crate::expressions::index::index_data;
+use crate::interpreter::Interpreter;
+use crate::operations::Operation;
+use crate::types::Type;
+
+pub use compiler::compile;
+
+pub mod compiler;
+
+/// A single operation of the VM, working on a stack of values. Names and constants are indices
+/// into the [`Chunk`] the instruction is part of.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    Constant(usize),
+    /// Pushes the value of a variable
+    Load(Variable),
+    /// Pops a value and declares a variable with it
+    Let(Variable),
+    /// Pops a value and assigns it to an existing variable, pushing it back
+    Assign(Variable),
+    /// Pops a value and adds it to an existing variable, pushing the sum
+    AddAssign(Variable),
+    /// Pops the right hand side and then the left hand side
+    Binary(Operation),
+    /// Pops a string or list and then the index into it
+    Index,
+    /// Pops the given number of values into a list, the first item is the deepest
+    List(usize),
+    /// Pops a record or host value and pushes one of its fields
+    Member(usize),
+    /// Pops a host value and then the new value of one of its fields, pushing the value back
+    SetMember(usize),
+    /// Pops the given number of arguments and then the function to call with them
+    Call(usize),
+    Pop,
+    Swap,
+    /// Empties the given number of slots from the first one, when the block declaring them ends
+    ClearLocals(usize, usize),
+    Jump(usize),
+    /// Pops a bool and jumps when it's false
+    JumpIfFalse(usize),
+    /// Pops the end and then the start of a range, pushing them back as whole numbers
+    ForRange,
+    /// With the next index and end of a range on top of the stack, stores the index in the slot
+    /// of the loop variable and advances it, or pops both and jumps once the range is done
+    ForNext(usize, usize),
+    /// Returns the value on top of the stack to the caller
+    Return
+}
+
+/// Where an instruction finds a variable, worked out by the compiler
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Variable {
+    /// A slot in the frame of the function being run
+    Local(usize),
+    /// The name of a variable in the global scope of the interpreter's memory, used outside of
+    /// functions
+    Global(usize),
+    /// The name of a variable a function uses without declaring it. Like with the tree walking
+    /// interpreter that's the innermost one of its callers, or else one in the interpreter's
+    /// memory.
+    Dynamic(usize)
+}
+
+/// Compiled code with the constants and names its instructions refer to
+#[derive(Debug, Clone, Default)]
+pub struct Chunk {
+    pub code: Vec<Instruction>,
+    pub constants: Vec<Data>,
+    pub names: Vec<String>,
+    /// Variables stored in the slots of the frame, so functions called from it can find them
+    pub locals: Vec<Local>,
+    /// Slots the frame needs
+    pub slot_count: usize
+}
+
+/// A variable stored in a slot of a frame
+#[derive(Debug, Clone)]
+pub struct Local {
+    pub name: String,
+    pub slot: usize,
+    /// First instruction run after the variable is declared
+    pub start: usize,
+    /// First instruction after the block that declares it
+    pub end: usize
+}
+
+impl Chunk {
+    /// Slot of the innermost variable called `name` declared while the instruction at `at` runs
+    fn live_slot(&self, name: &str, at: usize) -> Option<usize> {
+        self.locals.iter()
+            .filter(|local| local.name == name && (local.start..local.end).contains(&at))
+            .map(|local| local.slot)
+            .max()
+    }
+}
+
+/// A compiled function, or a whole program when it's the one returned by [`compile`]
+#[derive(Debug)]
+pub struct Function {
+    pub name: String,
+    pub args: Vec<(String, Type)>,
+    pub return_type: Type,
+    pub chunk: Chunk
+}
+
+impl Function {
+    pub fn get_type(&self) -> Type {
+        Type::Fn { args_types: self.args.clone(), return_type: Box::new(self.return_type.clone()) }
+    }
+}
+
+/// Runs a compiled program in the current scope of the interpreter's memory, the same way
+/// [`Interpreter::run_program`] runs the statements it was compiled from.
+///
+/// Variables declared by functions and blocks live in the VM's own frames instead of the
+/// interpreter's memory, so functions run by the tree walking interpreter that are called from
+/// VM code only see its globals.
+pub fn run(interpreter: &mut Interpreter, program: &Rc<Function>) -> Result<(), RuntimeErr> {
+    let mut vm = Vm::new(interpreter);
+    let base = vm.alloc_frame(program);
+    vm.execute(Frame { function: Rc::clone(program), ip: 0, base, is_call: false }).map(|_| ())
+}
+
+/// Calls a function value with already evaluated arguments
+pub fn call(interpreter: &mut Interpreter, function: &Rc<Function>, args: Vec<Data>) -> Result<Data, RuntimeErr> {
+    let mut vm = Vm::new(interpreter);
+    let base = vm.enter(function, args)?;
+    vm.execute(Frame { function: Rc::clone(function), ip: 0, base, is_call: true })
+}
+
+struct Frame {
+    function: Rc<Function>,
+    ip: usize,
+    /// Index of the frame's first slot in [`Vm::locals`]
+    base: usize,
+    /// Whether the frame is a function call, rather than a program
+    is_call: bool
+}
+
+/// Where a variable of the function being run is stored
+#[derive(Clone, Copy)]
+enum Place<'n> {
+    /// Index into [`Vm::locals`]
+    Local(usize),
+    /// Global scope of the interpreter's memory
+    Global(&'n str),
+    /// Innermost scope of the interpreter's memory declaring the name
+    Memory(&'n str)
+}
+
+struct Vm<'i, 'a, 'm> {
+    interpreter: &'i mut Interpreter<'a, 'm>,
+    stack: Vec<Data>,
+    /// Slots of every frame, each frame's from its base up to the next one's
+    locals: Vec<Data>,
+    /// Frames of the callers of the function being run
+    frames: Vec<Frame>,
+    /// Calls entered by this VM that haven't returned yet
+    calls: usize
+}
+
+impl<'i, 'a, 'm> Vm<'i, 'a, 'm> {
+    fn new(interpreter: &'i mut Interpreter<'a, 'm>) -> Self {
+        Self {
+            interpreter,
+            stack: vec![],
+            locals: vec![],
+            frames: vec![],
+            calls: 0
+        }
+    }
+
+    /// Adds empty slots for a frame of `function`, returning its base
+    fn alloc_frame(&mut self, function: &Function) -> usize {
+        let base = self.locals.len();
+        self.locals.resize(base + function.chunk.slot_count, Data::TempNil);
+        base
+    }
+
+    /// Drops the slots of the frame at `base`
+    fn free_frame(&mut self, base: usize) {
+        self.interpreter.free_memory(self.locals[base..].iter());
+        self.locals.truncate(base);
+    }
+
+    /// Sets up the frame of a call with the arguments in its first slots, like
+    /// [`Interpreter::call_fn`] does with a scope
+    fn enter(&mut self, function: &Function, args: Vec<Data>) -> Result<usize, RuntimeErr> {
+        self.interpreter.enter_call()?;
+        self.calls += 1;
+
+        let base = self.alloc_frame(function);
+        for (i, arg_data) in args.into_iter().enumerate() {
+            if let Err(err) = self.interpreter.store_memory(&arg_data, |_| 0) {
+                self.leave(base);
+                return Err(err);
+            }
+            self.locals[base + i] = arg_data;
+        }
+
+        Ok(base)
+    }
+
+    fn leave(&mut self, base: usize) {
+        self.free_frame(base);
+        self.interpreter.exit_call();
+        self.calls -= 1;
+    }
+
+    /// Runs until the entry frame returns, if it fails the frames left behind are dropped
+    fn execute(&mut self, entry: Frame) -> Result<Data, RuntimeErr> {
+        let res = self.run_frames(entry);
+
+        if res.is_err() {
+            self.free_frame(0);
+            for _ in 0..self.calls {
+                self.interpreter.exit_call();
+            }
+        }
+
+        res
+    }
+
+    fn pop(&mut self) -> Data {
+        self.stack.pop().expect("the compiler should keep the stack balanced")
+    }
+
+    /// Index in [`Vm::locals`] of the innermost variable called `name` declared by the function
+    /// being run at `ip` or one of its callers
+    fn find_local(&self, function: &Function, ip: usize, base: usize, name: &str) -> Option<usize> {
+        std::iter::once((function, ip, base))
+            .chain(self.frames.iter().rev().map(|frame| (frame.function.as_ref(), frame.ip, frame.base)))
+            // the instruction being run, or the call a caller is waiting on, is the one before `ip`
+            .find_map(|(function, ip, base)| function.chunk.live_slot(name, ip - 1).map(|slot| base + slot))
+    }
+
+    fn place<'n>(&self, variable: Variable, function: &'n Function, ip: usize, base: usize) -> Place<'n> {
+        match variable {
+            Variable::Local(slot) => Place::Local(base + slot),
+            Variable::Global(i) => Place::Global(&function.chunk.names[i]),
+            Variable::Dynamic(i) => {
+                let name = &function.chunk.names[i];
+
+                match self.find_local(function, ip, base, name) {
+                    Some(index) => Place::Local(index),
+                    None => Place::Memory(name)
+                }
+            }
+        }
+    }
+
+    fn get(&self, place: Place) -> &Data {
+        match place {
+            Place::Local(index) => Some(&self.locals[index]),
+            Place::Global(name) => self.interpreter.memory.scopes[0].get(name),
+            Place::Memory(name) => self.interpreter.memory.get(name)
+        }.expect("variables should have been resolved by the parser")
+    }
+
+    fn get_mut(&mut self, place: Place) -> &mut Data {
+        match place {
+            Place::Local(index) => Some(&mut self.locals[index]),
+            Place::Global(name) => self.interpreter.memory.scopes[0].get_mut(name),
+            Place::Memory(name) => self.interpreter.memory.get_at_mut(name, None)
+        }.expect("variables should have been resolved by the parser")
+    }
+
+    /// Replaces the value of an existing variable
+    fn store(&mut self, place: Place, data: Data) -> Result<(), RuntimeErr> {
+        match place {
+            Place::Local(index) => self.interpreter.store_memory(&data, |_| self.locals[index].heap_size())?,
+            Place::Global(name) => self.interpreter.store_memory(&data, |memory| {
+                memory.scopes[0].get(name).map_or(0, Data::heap_size)
+            })?,
+            Place::Memory(name) => self.interpreter.store_memory(&data, |memory| {
+                memory.get(name).map_or(0, Data::heap_size)
+            })?
+        }
+
+        *self.get_mut(place) = data;
+        Ok(())
+    }
+
+    fn run_frames(&mut self, entry: Frame) -> Result<Data, RuntimeErr> {
+        let Frame { mut function, mut ip, mut base, mut is_call } = entry;
+
+        loop {
+            self.interpreter.step()?;
+
+            let instruction = function.chunk.code[ip];
+            ip += 1;
+
+            match instruction {
+                Instruction::Constant(i) => self.stack.push(function.chunk.constants[i].clone()),
+                Instruction::Load(variable) => {
+                    let place = self.place(variable, &function, ip, base);
+                    let data = self.get(place).clone();
+                    self.stack.push(data);
+                },
+                Instruction::Let(Variable::Local(slot)) => {
+                    let data = self.pop();
+                    self.store(Place::Local(base + slot), data)?;
+                },
+                Instruction::Let(Variable::Global(i)) => {
+                    let data = self.pop();
+                    let name = &function.chunk.names[i];
+
+                    // redeclaring a name in the same scope replaces its value
+                    self.interpreter.store_memory(&data, |memory| {
+                        memory.scopes.last().unwrap().get(name).map_or(0, Data::heap_size)
+                    })?;
+                    self.interpreter.memory.insert(name.to_string(), data);
+                },
+                Instruction::Let(Variable::Dynamic(_)) => unreachable!("declarations are always local or global"),
+                Instruction::Assign(variable) => {
+                    let data = self.pop();
+                    let place = self.place(variable, &function, ip, base);
+
+                    self.store(place, data.clone())?;
+                    self.stack.push(data);
+                },
+                Instruction::AddAssign(variable) => {
+                    let value = self.pop();
+                    let place = self.place(variable, &function, ip, base);
+
+                    // grows the variable by the size of the value, see `ExprAssign::eval`
+                    self.interpreter.store_memory(&value, |_| 0)?;
+                    let data = self.get_mut(place);
+                    Operation::Add.op_assign(data, &value);
+                    let data = data.clone();
+                    self.stack.push(data);
+                },
+                Instruction::Binary(operation) => {
+                    let rhs = self.pop();
+                    let lhs = self.pop();
+                    self.stack.push(operation.op(&lhs, &rhs));
+                },
+                Instruction::Index => {
+                    let value = self.pop();
+                    let index = self.pop();
+                    self.stack.push(index_data(value, index)?);
+                },
+                Instruction::List(len) => {
+                    let items = self.stack.split_off(self.stack.len() - len);
+                    self.stack.push(Data::List(Rc::new(items)));
+                },
+                Instruction::Member(i) => {
+                    let field_name = &function.chunk.names[i];
+                    let data = match self.pop() {
+                        Data::Record(fields) => fields.iter()
+                            .find(|(name, _)| name == field_name)
+                            .map(|(_, field)| field.clone())
+                            .unwrap(),
+                        Data::Host(object) => object.get(field_name),
+                        _ => unreachable!()
+                    };
+                    self.stack.push(data);
+                },
+                Instruction::SetMember(i) => {
+                    let object = self.pop();
+                    let data = self.pop();
+
+                    match object {
+                        Data::Host(object) => object.set(&function.chunk.names[i], data.clone()),
+                        _ => unreachable!("should have already been caught in parsing")
+                    }
+                    self.stack.push(data);
+                },
+                Instruction::Call(arg_count) => {
+                    let args = self.stack.split_off(self.stack.len() - arg_count);
+
+                    let data = match self.pop() {
+                        Data::VmFn(callee) => {
+                            let callee_base = self.enter(&callee, args)?;
+
+                            let caller = std::mem::replace(&mut function, callee);
+                            self.frames.push(Frame { function: caller, ip, base, is_call });
+                            (ip, base, is_call) = (0, callee_base, true);
+                            continue;
+                        },
+                        Data::Fn(fn_decl) => self.interpreter.call_fn(&fn_decl, args)?,
+                        Data::BuiltinFn(builtin_fn) => builtin_fn.eval(args, self.interpreter)?,
+                        Data::HostFn(host_fn) => {
+                            let data = host_fn.call(args);
+                            self.interpreter.check_memory(&data)?;
+                            data
+                        },
+                        _ => unreachable!()
+                    };
+                    self.stack.push(data);
+                },
+                Instruction::Pop => {
+                    self.pop();
+                },
+                Instruction::Swap => {
+                    let len = self.stack.len();
+                    self.stack.swap(len - 1, len - 2);
+                },
+                Instruction::ClearLocals(first, count) => {
+                    let slots = &mut self.locals[base + first..base + first + count];
+                    self.interpreter.free_memory(slots.iter());
+                    slots.fill(Data::TempNil);
+                },
+                Instruction::Jump(target) => ip = target,
+                Instruction::JumpIfFalse(target) => match self.pop() {
+                    Data::Bool(true) => (),
+                    Data::Bool(false) => ip = target,
+                    _ => unreachable!()
+                },
+                Instruction::ForRange => {
+                    let end = self.pop();
+                    let start = self.pop();
+
+                    if let (Data::Number(start), Data::Number(end)) = (start, end) {
+                        self.stack.push(Data::Number(start as usize as f64));
+                        self.stack.push(Data::Number(end as usize as f64));
+                    }
+                },
+                Instruction::ForNext(slot, exit) => {
+                    let len = self.stack.len();
+
+                    match (&self.stack[len - 2], &self.stack[len - 1]) {
+                        (Data::Number(next), Data::Number(end)) if next < end => {
+                            let next = *next;
+                            self.stack[len - 2] = Data::Number(next + 1.0);
+                            self.locals[base + slot] = Data::Number(next);
+                        },
+                        _ => {
+                            self.stack.truncate(len - 2);
+                            ip = exit;
+                        }
+                    }
+                },
+                Instruction::Return => {
+                    let value = self.pop();
+
+                    match is_call {
+                        true => self.leave(base),
+                        false => self.free_frame(base)
+                    }
+
+                    match self.frames.pop() {
+                        Some(caller) => {
+                            Frame { function, ip, base, is_call } = caller;
+                            self.stack.push(value);
+                        },
+                        None => return Ok(value)
+                    }
+                }
+            }
+        }
+    }
+}